@@ -1,20 +1,32 @@
+use std::borrow::Borrow;
 use std::fmt;
-use std::io::{self, Write};
+use std::io;
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, ValueEnum};
 use mersenne_twister_rs::MersenneTwister64;
 use rand::Rng;
-use rayon::prelude::*;
 
-use hypervector::datasets::har_dataset::{Dataset, Label, N_FEATURES, NUM_CLASSES};
-use hypervector::encoding::{BundleEncoder, FromSpectrum, FwhtEncoder};
+use hypervector::datasets::har_dataset::{
+    Dataset, InertialDataset, Label, N_CHANNELS, N_FEATURES, NUM_CLASSES,
+};
+use hypervector::encoding::timeseries::TimeSeriesEncoder;
+use hypervector::encoding::{
+    BundleEncoder, Encoder as HvEncoder, FromPhases, FromSpectrum, FwhtEncoder, Kernel, RffEncoder,
+    ScalarFit,
+};
 use hypervector::hdv;
+use hypervector::metrics::ConfusionMatrix;
 use hypervector::trainer::{
-    Classifier, Trainer, ensemble_accuracy,
+    Classifier, EpochResult, MultiPrototypeModel,
+    driver::{Driver, Fitted},
+    ensemble_accuracy, ensemble_vote,
     lvq::LvqTrainer,
+    model_selection::Summary,
     multi_perceptron::PerceptronMultiTrainer,
     pa::{PaTrainer, PaVariant},
     perceptron::PerceptronTrainer,
+    persist::{ModelInfo, Persist},
 };
 use hypervector::types::traits::HyperVector;
 use hypervector::types::{binary::Binary, complex::ComplexHDV, modular::Modular, real::RealHDV};
@@ -46,6 +58,11 @@ impl fmt::Display for TrainerKind {
 enum Encoder {
     Bundle,
     Fwht,
+    // random Fourier features
+    Rbf,
+    Laplacian,
+    // raw inertial signals
+    Series,
 }
 
 impl fmt::Display for Encoder {
@@ -53,11 +70,14 @@ impl fmt::Display for Encoder {
         match self {
             Encoder::Bundle => write!(f, "bundle"),
             Encoder::Fwht => write!(f, "fwht"),
+            Encoder::Rbf => write!(f, "rbf"),
+            Encoder::Laplacian => write!(f, "laplacian"),
+            Encoder::Series => write!(f, "series"),
         }
     }
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(long, default_value = "binary", value_parser=["binary", "bipolar", "real", "complex", "modular"])]
@@ -73,6 +93,14 @@ struct Args {
     #[arg(long, default_value_t = Encoder::Fwht)]
     encoder: Encoder,
 
+    #[arg(long, default_value_t = 10.0)]
+    /// kernel bandwidth of the rbf and laplacian encoders
+    bandwidth: f32,
+
+    #[arg(long, default_value_t = 3)]
+    /// timesteps per temporal n-gram of the series encoder
+    ngram: usize,
+
     #[arg(long, default_value_t = 1)]
     /// number of prototypes per class
     prototypes: usize,
@@ -86,6 +114,64 @@ struct Args {
 
     #[arg(long, default_value_t = 9)]
     ensemble_size: usize,
+
+    #[arg(long, default_value_t = 1)]
+    /// model i is generated from seed + i
+    seed: u64,
+
+    #[arg(long)]
+    /// directory to save the trained models in
+    save: Option<String>,
+
+    #[arg(long)]
+    /// directory with models saved earlier - evaluate them instead of training
+    load: Option<String>,
+}
+
+impl Args {
+    fn kernel(&self) -> Kernel {
+        let bandwidth = self.bandwidth;
+        match self.encoder {
+            Encoder::Laplacian => Kernel::Laplacian { bandwidth },
+            _ => Kernel::Rbf { bandwidth },
+        }
+    }
+
+    /// What is needed to rebuild the encoder, and how the model was trained
+    fn model_info(&self, seed: u64, history: &[EpochResult]) -> ModelInfo {
+        ModelInfo::new(&self.encoder.to_string(), seed)
+            .with_class_names(&class_names())
+            .with_metadata("mode", &self.mode)
+            .with_metadata("dim", self.dim)
+            .with_metadata("trainer", self.trainer)
+            .with_metadata("bandwidth", self.bandwidth)
+            .with_metadata("ngram", self.ngram)
+            .with_history(history)
+    }
+
+    /// The encoder settings of a saved model
+    fn with_model_info(&self, info: &ModelInfo) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut args = self.clone();
+        args.encoder = Encoder::from_str(&info.encoder, false).map_err(invalid)?;
+        if let Some(b) = info.get("bandwidth") {
+            args.bandwidth = b.parse().map_err(|e| invalid(format!("bandwidth: {e}")))?;
+        }
+        if let Some(n) = info.get("ngram") {
+            args.ngram = n.parse().map_err(|e| invalid(format!("ngram: {e}")))?;
+        }
+        Ok(args)
+    }
+}
+
+fn class_names() -> Vec<String> {
+    (1..=NUM_CLASSES as u8)
+        .map(|n| format!("{:?}", Label::from_label(n)))
+        .collect()
+}
+
+fn model_path(dir: &str, i: usize) -> PathBuf {
+    Path::new(dir).join(format!("har_{i}.hdv"))
 }
 
 fn valid_dim(s: &str) -> Result<usize, String> {
@@ -112,48 +198,110 @@ hdv!(real, RealHDV1024, 1024);
 hdv!(real, RealHDV2048, 2048);
 hdv!(complex, ComplexHDV1024, 1024);
 
-fn train<T, Tr>(mut trainer: Tr, epochs: usize) -> Tr::Model
+/// Saves the model if --save is given, and classifies the test set
+fn finish<T, M>(
+    fitted: Fitted<M>,
+    test_hvs: &[T],
+    i: usize,
+    seed: u64,
+    args: &Args,
+) -> io::Result<Vec<usize>>
 where
-    T: HyperVector,
-    Tr: Trainer<T>,
+    T: HyperVector + Sync + Send,
+    M: Classifier<T> + Persist + Sync,
 {
-    for epoch in 1..=epochs {
-        let r = trainer.step(epoch);
-        print!(
-            "Epoch {epoch}: Training Accuracy {}/{}={:.2}%\r",
-            r.correct,
-            r.total(),
-            r.accuracy() * 100.0
-        );
-        std::io::stdout().flush().unwrap();
-        if r.errors == 0 {
-            break;
-        }
+    if let Some(dir) = &args.save {
+        std::fs::create_dir_all(dir)?;
+        let info = args.model_info(seed, &fitted.history());
+        fitted.model.save(model_path(dir, i), &info)?;
     }
-    println!();
-    trainer.into_model()
+    Ok(fitted.model.classify_all(test_hvs))
 }
 
-fn run<T: HyperVector + Sync + Send>(data: &Dataset, rng: &mut impl Rng, args: &Args) -> Vec<usize>
+fn encode_sets<E, I, S, T>(
+    encoder: &E,
+    train: &[S],
+    test: &[S],
+    with_train: bool,
+) -> (Vec<T>, Vec<T>)
 where
-    T: FromSpectrum,
+    E: HvEncoder<I, T> + Sync,
+    I: ?Sized,
+    S: Borrow<I> + Sync,
+    T: HyperVector + Send,
 {
-    let (train_hvs, test_hvs): (Vec<T>, Vec<T>) = if args.encoder == Encoder::Bundle {
-        let encoder = BundleEncoder::<T, N_FEATURES>::new(rng);
-        (
-            data.train.par_iter().map(|s| encoder.encode(s)).collect(),
-            data.test.par_iter().map(|s| encoder.encode(s)).collect(),
-        )
+    let train = if with_train {
+        encoder.encode_batch(train)
     } else {
-        let encoder = FwhtEncoder::<T, N_FEATURES>::new(rng);
-        (
-            data.train.par_iter().map(|s| encoder.encode(s)).collect(),
-            data.test.par_iter().map(|s| encoder.encode(s)).collect(),
-        )
+        Vec::new()
     };
+    (train, encoder.encode_batch(test))
+}
+
+/// Train and test set encoded - the train set only if `with_train`
+fn encode<T>(
+    data: &Dataset,
+    inertial: Option<&InertialDataset>,
+    rng: &mut impl Rng,
+    args: &Args,
+    with_train: bool,
+) -> (Vec<T>, Vec<T>)
+where
+    T: HyperVector + FromSpectrum + FromPhases + Sync + Send,
+{
+    match args.encoder {
+        Encoder::Bundle => {
+            let encoder = BundleEncoder::<T, N_FEATURES>::new(rng);
+            encode_sets(&encoder, &data.train, &data.test, with_train)
+        }
+        Encoder::Fwht => {
+            let encoder = FwhtEncoder::<T, N_FEATURES>::new(rng);
+            encode_sets(&encoder, &data.train, &data.test, with_train)
+        }
+        Encoder::Rbf | Encoder::Laplacian => {
+            let encoder = RffEncoder::<T, N_FEATURES>::new(args.kernel(), rng);
+            encode_sets(&encoder, &data.train, &data.test, with_train)
+        }
+        Encoder::Series => {
+            let inertial = inertial.expect("series encoder needs the inertial signals");
+            let fit = ScalarFit::new(32).with_percentiles(1.0, 99.0);
+            let encoder =
+                TimeSeriesEncoder::<T, N_CHANNELS>::fit(&inertial.train, &fit, args.ngram, rng);
+            encode_sets(&encoder, &inertial.train, &inertial.test, with_train)
+        }
+    }
+}
+
+/// Trains (or loads) model i and classifies the test set
+fn run<T>(
+    data: &Dataset,
+    inertial: Option<&InertialDataset>,
+    i: usize,
+    args: &Args,
+) -> io::Result<Vec<usize>>
+where
+    T: HyperVector + FromSpectrum + FromPhases + Sync + Send,
+{
+    if let Some(dir) = &args.load {
+        // the encoder is rebuilt from the saved seed and settings
+        let (model, info) = MultiPrototypeModel::<T>::load(model_path(dir, i))?;
+        let args = args.with_model_info(&info)?;
+        if args.encoder == Encoder::Series && inertial.is_none() {
+            return Err(io::Error::other(
+                "model uses the series encoder - pass --encoder series to load the inertial signals",
+            ));
+        }
+        let mut rng = MersenneTwister64::new(info.seed);
+        let (_, test_hvs) = encode::<T>(data, inertial, &mut rng, &args, false);
+        return Ok(model.classify_all(&test_hvs));
+    }
+
+    let seed = args.seed + i as u64;
+    let rng = &mut MersenneTwister64::new(seed);
+    let (train_hvs, test_hvs) = encode::<T>(data, inertial, rng, args, true);
 
     let k = args.prototypes;
-    let epochs = args.epochs;
+    let driver = Driver::new(args.epochs).with_progress();
 
     match args.trainer {
         TrainerKind::Perceptron => {
@@ -163,7 +311,7 @@ where
                 None,
                 &mut *rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
         TrainerKind::Pa => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -173,7 +321,7 @@ where
                 PaVariant::Pa,
                 &mut *rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
         TrainerKind::Pai => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -183,7 +331,7 @@ where
                 PaVariant::PaI { c: 0.1 },
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
         TrainerKind::Paii => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -193,7 +341,7 @@ where
                 PaVariant::PaII { c: 1.0 },
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
         TrainerKind::Multi => {
             let trainer = PerceptronMultiTrainer::<T, _>::new(
@@ -204,7 +352,7 @@ where
                 k,
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
         TrainerKind::Lvq => {
             let trainer = LvqTrainer::<T, _>::new(
@@ -216,58 +364,38 @@ where
                 rng,
                 args.window,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            finish(driver.run(trainer), &test_hvs, i, seed, args)
         }
     }
 }
 
-fn stats(v: &[f64]) -> Option<(f64, f64, f64)> {
-    if v.is_empty() {
-        return None;
-    }
-
-    let mut min = v[0];
-    let mut max = v[0];
-    let mut sum = v[0];
-
-    for &x in &v[1..] {
-        if x < min {
-            min = x;
-        }
-        if x > max {
-            max = x;
-        }
-        sum += x;
-    }
-
-    let avg = sum / v.len() as f64;
-    Some((100.0 * min, 100.0 * max, 100.0 * avg))
-}
-
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
     let ensemble_size = args.ensemble_size;
     let har = Dataset::load("UCI HAR Dataset")?;
-
-    let mut rng = MersenneTwister64::default();
+    let inertial = match args.encoder {
+        Encoder::Series => Some(InertialDataset::load("UCI HAR Dataset")?),
+        _ => None,
+    };
+    let inertial = inertial.as_ref();
 
     let mut all_predictions = Vec::with_capacity(ensemble_size);
     let mut accs = Vec::with_capacity(ensemble_size);
     for i in 1..=ensemble_size {
         let preds = match (args.mode.as_str(), args.dim) {
-            ("binary", 1024) => run::<BinaryHDV1024>(&har, &mut rng, &args),
-            ("binary", 2048) => run::<BinaryHDV2048>(&har, &mut rng, &args),
-            ("binary", 4096) => run::<BinaryHDV4096>(&har, &mut rng, &args),
-            ("binary", 8192) => run::<BinaryHDV8192>(&har, &mut rng, &args),
-            ("binary", 16384) => run::<BinaryHDV16384>(&har, &mut rng, &args),
-            ("modular", 1024) => run::<ModularHDV1024>(&har, &mut rng, &args),
-            ("modular", 2048) => run::<ModularHDV2048>(&har, &mut rng, &args),
-            ("modular", 4096) => run::<ModularHDV4096>(&har, &mut rng, &args),
-            ("modular", 8192) => run::<ModularHDV8192>(&har, &mut rng, &args),
-            ("modular", 16384) => run::<ModularHDV16384>(&har, &mut rng, &args),
-            ("real", 1024) => run::<RealHDV1024>(&har, &mut rng, &args),
-            ("real", 2048) => run::<RealHDV2048>(&har, &mut rng, &args),
-            ("complex", 1024) => run::<ComplexHDV1024>(&har, &mut rng, &args),
+            ("binary", 1024) => run::<BinaryHDV1024>(&har, inertial, i, &args)?,
+            ("binary", 2048) => run::<BinaryHDV2048>(&har, inertial, i, &args)?,
+            ("binary", 4096) => run::<BinaryHDV4096>(&har, inertial, i, &args)?,
+            ("binary", 8192) => run::<BinaryHDV8192>(&har, inertial, i, &args)?,
+            ("binary", 16384) => run::<BinaryHDV16384>(&har, inertial, i, &args)?,
+            ("modular", 1024) => run::<ModularHDV1024>(&har, inertial, i, &args)?,
+            ("modular", 2048) => run::<ModularHDV2048>(&har, inertial, i, &args)?,
+            ("modular", 4096) => run::<ModularHDV4096>(&har, inertial, i, &args)?,
+            ("modular", 8192) => run::<ModularHDV8192>(&har, inertial, i, &args)?,
+            ("modular", 16384) => run::<ModularHDV16384>(&har, inertial, i, &args)?,
+            ("real", 1024) => run::<RealHDV1024>(&har, inertial, i, &args)?,
+            ("real", 2048) => run::<RealHDV2048>(&har, inertial, i, &args)?,
+            ("complex", 1024) => run::<ComplexHDV1024>(&har, inertial, i, &args)?,
             _ => {
                 eprintln!(
                     "Unsupported combination: mode={} dim={}",
@@ -298,8 +426,16 @@ fn main() -> Result<(), io::Error> {
         println!();
     }
 
-    if let Some((min, max, avg)) = stats(&accs) {
-        println!("Model accuracies - avg {avg:.2}%, min {min:.2}%, max {max:.2}")
+    if !all_predictions.is_empty() {
+        let votes = ensemble_vote::<Label>(&all_predictions, NUM_CLASSES);
+        let cm = ConfusionMatrix::from_predictions(&votes, &har.test_labels, NUM_CLASSES);
+        println!(
+            "Ensemble of {ensemble_size}:\n{}\n",
+            cm.report(&class_names())
+        );
+    }
+    if let Some(summary) = Summary::new(&accs) {
+        println!("Model accuracies - {summary}")
     }
 
     Ok(())
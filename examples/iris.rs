@@ -2,7 +2,7 @@ use rand::Rng;
 
 use hypervector::encoding::TabularEncoder;
 use hypervector::hdv;
-use hypervector::trainer::{Classifier, Trainer, multi_perceptron::PerceptronMultiTrainer};
+use hypervector::trainer::{Classifier, driver::Driver, multi_perceptron::PerceptronMultiTrainer};
 use hypervector::types::binary::Binary;
 use hypervector::types::traits::HyperVector;
 use mersenne_twister_rs::MersenneTwister64;
@@ -193,20 +193,6 @@ const VIRGINICA: [[f32; 4]; 50] = [
 //    println!("max: {:?}", max_vals);
 //}
 
-fn train<T, Tr>(mut trainer: Tr, epochs: usize) -> Tr::Model
-where
-    T: HyperVector,
-    Tr: Trainer<T>,
-{
-    for epoch in 1..=epochs {
-        let r = trainer.step(epoch);
-        if r.errors == 0 {
-            break;
-        }
-    }
-    trainer.into_model()
-}
-
 fn run<T: HyperVector + Send + Sync>(data: &[T], labels: &[u8], rng: &mut impl Rng) {
     let epochs = 5;
     const NUM_CLASSES: usize = 3;
@@ -224,7 +210,7 @@ fn run<T: HyperVector + Send + Sync>(data: &[T], labels: &[u8], rng: &mut impl R
             &mut *rng,
         );
 
-        let model = train(trainer, epochs);
+        let model = Driver::new(epochs).run(trainer).model;
         if model.predict(&data[i]) == labels[i] as usize {
             correct += 1;
         }
@@ -253,7 +239,7 @@ fn main() {
         .iter()
         .chain(VERSICOLOR.iter())
         .chain(VIRGINICA.iter())
-        .map(|s| encoder.encode(s))
+        .map(|s| encoder.encode_values(s))
         .collect();
     let labels: Vec<u8> = (0..50)
         .map(|_| 0)
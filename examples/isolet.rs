@@ -1,19 +1,19 @@
 use clap::{Parser, ValueEnum};
 use hypervector::datasets::isolet_dataset::{Dataset, Label, N_FEATURES, NUM_CLASSES};
-use hypervector::encoding::BundleEncoder;
+use hypervector::encoding::{BundleEncoder, Encoder as _, FromPhases, Kernel, RffEncoder};
 use hypervector::hdv;
+use hypervector::metrics::ConfusionMatrix;
 use hypervector::trainer::{
-    Classifier, Trainer, ensemble_accuracy, lvq::LvqTrainer,
-    multi_perceptron::PerceptronMultiTrainer, pa::PaTrainer, pa::PaVariant,
-    perceptron::PerceptronTrainer,
+    Classifier, driver::Driver, ensemble_accuracy, ensemble_vote, lvq::LvqTrainer,
+    model_selection::Summary, multi_perceptron::PerceptronMultiTrainer, pa::PaTrainer,
+    pa::PaVariant, perceptron::PerceptronTrainer,
 };
 use hypervector::types::traits::HyperVector;
 use hypervector::types::{binary::Binary, complex::ComplexHDV, modular::Modular, real::RealHDV};
 use mersenne_twister_rs::MersenneTwister64;
 use rand::Rng;
-use rayon::prelude::*;
 use std::fmt;
-use std::io::{self, Write};
+use std::io;
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum TrainerKind {
@@ -38,6 +38,24 @@ impl fmt::Display for TrainerKind {
     }
 }
 
+#[derive(Debug, Clone, ValueEnum, PartialEq)]
+enum Encoder {
+    Bundle,
+    // random Fourier features
+    Rbf,
+    Laplacian,
+}
+
+impl fmt::Display for Encoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoder::Bundle => write!(f, "bundle"),
+            Encoder::Rbf => write!(f, "rbf"),
+            Encoder::Laplacian => write!(f, "laplacian"),
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -51,6 +69,13 @@ struct Args {
     #[arg(long, default_value_t = TrainerKind::Perceptron)]
     trainer: TrainerKind,
 
+    #[arg(long, default_value_t = Encoder::Bundle)]
+    encoder: Encoder,
+
+    #[arg(long, default_value_t = 10.0)]
+    /// kernel bandwidth of the rbf and laplacian encoders
+    bandwidth: f32,
+
     #[arg(long, default_value_t = 1)]
     /// number of prototypes per class
     prototypes: usize,
@@ -64,6 +89,20 @@ struct Args {
 
     #[arg(long, default_value_t = 9)]
     ensemble_size: usize,
+
+    #[arg(long)]
+    /// mini-batch size of the pa trainers - online training if not given
+    batch_size: Option<usize>,
+}
+
+impl Args {
+    fn kernel(&self) -> Kernel {
+        let bandwidth = self.bandwidth;
+        match self.encoder {
+            Encoder::Laplacian => Kernel::Laplacian { bandwidth },
+            _ => Kernel::Rbf { bandwidth },
+        }
+    }
 }
 
 fn valid_dim(s: &str) -> Result<usize, String> {
@@ -90,36 +129,27 @@ hdv!(real, RealHDV1024, 1024);
 hdv!(real, RealHDV2048, 2048);
 hdv!(complex, ComplexHDV1024, 1024);
 
-fn train<T, Tr>(mut trainer: Tr, epochs: usize) -> Tr::Model
-where
-    T: HyperVector,
-    Tr: Trainer<T>,
-{
-    for epoch in 1..=epochs {
-        let r = trainer.step(epoch);
-        print!(
-            "Epoch {epoch}: Training Accuracy {}/{}={:.2}%\r",
-            r.correct,
-            r.total(),
-            r.accuracy() * 100.0
-        );
-        std::io::stdout().flush().unwrap();
-        if r.errors == 0 {
-            break;
-        }
-    }
-    println!();
-    trainer.into_model()
-}
-
-fn run<T: HyperVector + Sync + Send>(
+fn run<T: HyperVector + FromPhases + Sync + Send>(
     data: &Dataset,
     rng: &mut impl Rng,
     args: &Args,
 ) -> Vec<usize> {
-    let encoder = BundleEncoder::<T, N_FEATURES>::new(rng);
-    let train_hvs: Vec<T> = data.train.par_iter().map(|s| encoder.encode(s)).collect();
-    let test_hvs: Vec<T> = data.test.par_iter().map(|s| encoder.encode(s)).collect();
+    let (train_hvs, test_hvs): (Vec<T>, Vec<T>) = match args.encoder {
+        Encoder::Bundle => {
+            let encoder = BundleEncoder::<T, N_FEATURES>::new(rng);
+            (
+                encoder.encode_batch(&data.train),
+                encoder.encode_batch(&data.test),
+            )
+        }
+        Encoder::Rbf | Encoder::Laplacian => {
+            let encoder = RffEncoder::<T, N_FEATURES>::new(args.kernel(), rng);
+            (
+                encoder.encode_batch(&data.train),
+                encoder.encode_batch(&data.test),
+            )
+        }
+    };
 
     let k = args.prototypes;
     let epochs = args.epochs;
@@ -132,7 +162,11 @@ fn run<T: HyperVector + Sync + Send>(
                 None,
                 &mut *rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Pa => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -142,7 +176,12 @@ fn run<T: HyperVector + Sync + Send>(
                 PaVariant::Pa,
                 &mut *rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            let trainer = trainer.with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Pai => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -152,7 +191,12 @@ fn run<T: HyperVector + Sync + Send>(
                 PaVariant::PaI { c: 0.1 },
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            let trainer = trainer.with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Paii => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -162,7 +206,12 @@ fn run<T: HyperVector + Sync + Send>(
                 PaVariant::PaII { c: 1.0 },
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            let trainer = trainer.with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Multi => {
             let trainer = PerceptronMultiTrainer::<T, _>::new(
@@ -173,7 +222,11 @@ fn run<T: HyperVector + Sync + Send>(
                 k,
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Lvq => {
             let trainer = LvqTrainer::<T, _>::new(
@@ -185,32 +238,13 @@ fn run<T: HyperVector + Sync + Send>(
                 rng,
                 args.window,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
-        }
-    }
-}
-
-fn stats(v: &[f64]) -> Option<(f64, f64, f64)> {
-    if v.is_empty() {
-        return None;
-    }
-
-    let mut min = v[0];
-    let mut max = v[0];
-    let mut sum = v[0];
-
-    for &x in &v[1..] {
-        if x < min {
-            min = x;
-        }
-        if x > max {
-            max = x;
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
-        sum += x;
     }
-
-    let avg = sum / v.len() as f64;
-    Some((100.0 * min, 100.0 * max, 100.0 * avg))
 }
 
 fn main() -> Result<(), io::Error> {
@@ -269,8 +303,14 @@ fn main() -> Result<(), io::Error> {
         println!();
     }
 
-    if let Some((min, max, avg)) = stats(&accs) {
-        println!("Model accuracies - avg {avg:.2}%, min {min:.2}%, max {max:.2}")
+    if !all_predictions.is_empty() {
+        let letters: Vec<String> = ('a'..='z').map(String::from).collect();
+        let votes = ensemble_vote::<Label>(&all_predictions, NUM_CLASSES);
+        let cm = ConfusionMatrix::from_predictions(&votes, &dataset.test_labels, NUM_CLASSES);
+        println!("Ensemble of {ensemble_size}:\n{}\n", cm.report(&letters));
+    }
+    if let Some(summary) = Summary::new(&accs) {
+        println!("Model accuracies - {summary}")
     }
 
     Ok(())
@@ -1,12 +1,10 @@
 use clap::Parser;
+use hypervector::encoding::Codebook;
 use hypervector::types::traits::{HyperVector, UnitAccumulator};
 use hypervector::types::{
     binary::Binary, binary::Bipolar, complex::ComplexHDV, modular::Modular, real::RealHDV,
 };
 use hypervector::{cleanup, save_hypervectors_to_csv};
-use mersenne_twister_rs::MersenneTwister64;
-use rand::Rng;
-use std::collections::hash_map::HashMap;
 use std::collections::vec_deque::VecDeque;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -24,6 +22,10 @@ struct Args {
 
     #[arg(long, default_value_t = 3)]
     ngram: usize,
+
+    #[arg(long, default_value_t = 42)]
+    /// seed of the symbol codebook
+    seed: u64,
 }
 
 const LANGUAGES: [&str; 22] = [
@@ -31,11 +33,10 @@ const LANGUAGES: [&str; 22] = [
     "pl", "pt", "ro", "sk", "sl", "sv",
 ];
 
-pub fn create_language_profile<T: HyperVector, R: Rng>(
+pub fn create_language_profile<T: HyperVector>(
     fname: &Path,
     n: usize,
-    symbols: &mut HashMap<char, T>,
-    rng: &mut R,
+    symbols: &mut Codebook<T>,
 ) -> Result<T, io::Error> {
     let file = File::open(fname)?;
     let reader = io::BufReader::new(file);
@@ -54,8 +55,7 @@ pub fn create_language_profile<T: HyperVector, R: Rng>(
             // for real and complex HDVs this doesn't work well because unbind is too noisy
             let mut ngram = T::ident();
             for &c in window.iter() {
-                let sym = symbols.entry(c).or_insert_with(|| T::random(rng));
-                ngram = ngram.permute(1).bind(sym);
+                ngram = ngram.permute(1).bind(symbols.get_char(c));
             }
             acc.add(&ngram);
         }
@@ -65,11 +65,10 @@ pub fn create_language_profile<T: HyperVector, R: Rng>(
 
 // like create_language_profile - use unbind to update ngrams
 // unbind is noisy for real/complex HDVs
-pub fn create_language_profile_bind<T: HyperVector, R: Rng>(
+pub fn create_language_profile_bind<T: HyperVector>(
     fname: &Path,
     n: usize,
-    symbols: &mut HashMap<char, T>,
-    rng: &mut R,
+    symbols: &mut Codebook<T>,
 ) -> Result<T, io::Error> {
     let file = File::open(fname)?;
     let reader = io::BufReader::new(file);
@@ -83,25 +82,23 @@ pub fn create_language_profile_bind<T: HyperVector, R: Rng>(
         let mut ngram = T::ident();
         let mut block: VecDeque<char> = VecDeque::with_capacity(n);
         for &c in &chars[..n] {
-            let sym = symbols.entry(c).or_insert_with(|| T::random(rng));
             block.push_front(c);
-            ngram = ngram.permute(1).bind(sym);
+            ngram = ngram.permute(1).bind(symbols.get_char(c));
         }
 
         acc.add(&ngram);
         for &c in &chars[n..] {
             let forget = block.pop_back().unwrap();
-            let forget_sym = symbols.get(&forget).unwrap();
+            let forget_sym = symbols.get_char(forget);
 
             // Unbind the oldest symbol - it has been permuted (n-1) times
             let to_remove = forget_sym.permute(n - 1);
             ngram = ngram.unbind(&to_remove);
 
-            let new_sym = symbols.entry(c).or_insert_with(|| T::random(rng));
             block.push_front(c);
 
             // Shift the remaining (n-1) symbols and bind the new one at position 0
-            ngram = ngram.permute(1).bind(new_sym);
+            ngram = ngram.permute(1).bind(symbols.get_char(c));
 
             acc.add(&ngram);
         }
@@ -109,29 +106,26 @@ pub fn create_language_profile_bind<T: HyperVector, R: Rng>(
     Ok(acc.finalize())
 }
 
-type SymbolMap<T> = HashMap<char, T>;
 type LanguageModel<T> = Vec<(&'static str, T)>;
 
-fn train<T: HyperVector, R: Rng>(
+fn train<T: HyperVector>(
     n: usize,
-    rng: &mut R,
-) -> Result<(SymbolMap<T>, LanguageModel<T>), io::Error> {
-    let mut symbols: HashMap<char, T> = HashMap::new();
+    symbols: &mut Codebook<T>,
+) -> Result<LanguageModel<T>, io::Error> {
     let mut languages: Vec<(&str, T)> = Vec::new();
     for (i, lxx) in LANGUAGES.iter().enumerate() {
         let fname = format!("DATA/LANG_ID/training_texts/{lxx}.txt");
         println!("{i}/{}: Processing training file {fname}", LANGUAGES.len());
-        let v = create_language_profile(Path::new(&fname), n, &mut symbols, rng)?;
+        let v = create_language_profile(Path::new(&fname), n, symbols)?;
         languages.push((lxx, v));
     }
-    Ok((symbols, languages))
+    Ok(languages)
 }
 
-fn test<T: HyperVector, R: Rng>(
-    symbols: &mut HashMap<char, T>,
+fn test<T: HyperVector>(
+    symbols: &mut Codebook<T>,
     languages: &[(&str, T)],
     n: usize,
-    rng: &mut R,
 ) -> Result<(), io::Error> {
     let mut total = 0;
     let mut correct = 0;
@@ -142,7 +136,7 @@ fn test<T: HyperVector, R: Rng>(
         let pattern = format!("DATA/LANG_ID/testing_texts/{lxx}_*.txt");
         for fname in glob::glob(&pattern).expect("wrong glob pattern") {
             let fname = fname.map_err(io::Error::other)?;
-            let v = create_language_profile(&fname, n, symbols, rng)?;
+            let v = create_language_profile(&fname, n, symbols)?;
             if cleanup(&v, languages) == *lxx {
                 correct += 1
             }
@@ -156,38 +150,40 @@ fn test<T: HyperVector, R: Rng>(
     Ok(())
 }
 
-fn run<T: HyperVector + Clone>(n: usize) -> Result<(), io::Error> {
-    let mut mt = MersenneTwister64::new(42);
-    let (mut symbols, languages) = train::<T, _>(n, &mut mt).expect("Training failed");
+fn run<T: HyperVector + Clone>(n: usize, seed: u64) -> Result<(), io::Error> {
+    // symbol vectors depend only on the seed, not on the order of the training files
+    let mut symbols = Codebook::<T>::new(seed);
+    let languages = train(n, &mut symbols).expect("Training failed");
     let model: Vec<T> = languages.iter().map(|(_label, hdv)| hdv.clone()).collect();
     save_hypervectors_to_csv("RESULTS/model.csv", &model)?;
-    test(&mut symbols, &languages, n, &mut mt)
+    test(&mut symbols, &languages, n)
 }
 
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
     let n = args.ngram;
+    let seed = args.seed;
     println!(
         "Mode: {} N-gram: {} Dim: {}",
         args.mode, args.ngram, args.dim
     );
     match (args.mode.as_str(), args.dim) {
-        ("binary", 1024) => run::<Binary<16>>(n)?,
-        ("binary", 10048) => run::<Binary<157>>(n)?,
-        ("binary", 100032) => run::<Binary<1563>>(n)?,
-        ("binary", 200000) => run::<Binary<3125>>(n)?,
-        ("bipolar", 1024) => run::<Bipolar<16>>(n)?,
-        ("bipolar", 10048) => run::<Bipolar<157>>(n)?,
-        ("bipolar", 100032) => run::<Bipolar<1563>>(n)?,
-        ("real", 1024) => run::<RealHDV<1024>>(n)?,
-        ("real", 10048) => run::<RealHDV<10048>>(n)?,
-        ("real", 100032) => run::<RealHDV<100032>>(n)?,
-        ("complex", 1024) => run::<ComplexHDV<1024>>(n)?,
-        ("complex", 10048) => run::<ComplexHDV<10048>>(n)?,
-        ("complex", 100032) => run::<ComplexHDV<100032>>(n)?,
-        ("modular", 1024) => run::<Modular<1024>>(n)?,
-        ("modular", 10048) => run::<Modular<10048>>(n)?,
-        ("modular", 100032) => run::<Modular<100032>>(n)?,
+        ("binary", 1024) => run::<Binary<16>>(n, seed)?,
+        ("binary", 10048) => run::<Binary<157>>(n, seed)?,
+        ("binary", 100032) => run::<Binary<1563>>(n, seed)?,
+        ("binary", 200000) => run::<Binary<3125>>(n, seed)?,
+        ("bipolar", 1024) => run::<Bipolar<16>>(n, seed)?,
+        ("bipolar", 10048) => run::<Bipolar<157>>(n, seed)?,
+        ("bipolar", 100032) => run::<Bipolar<1563>>(n, seed)?,
+        ("real", 1024) => run::<RealHDV<1024>>(n, seed)?,
+        ("real", 10048) => run::<RealHDV<10048>>(n, seed)?,
+        ("real", 100032) => run::<RealHDV<100032>>(n, seed)?,
+        ("complex", 1024) => run::<ComplexHDV<1024>>(n, seed)?,
+        ("complex", 10048) => run::<ComplexHDV<10048>>(n, seed)?,
+        ("complex", 100032) => run::<ComplexHDV<100032>>(n, seed)?,
+        ("modular", 1024) => run::<Modular<1024>>(n, seed)?,
+        ("modular", 10048) => run::<Modular<10048>>(n, seed)?,
+        ("modular", 100032) => run::<Modular<100032>>(n, seed)?,
         _ => {
             eprintln!("Unsupported combination: {args:?}");
             std::process::exit(1);
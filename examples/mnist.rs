@@ -1,13 +1,13 @@
 use clap::Parser;
-use hypervector::encoding::ScalarEncoder;
+use hypervector::encoding::{Encoder, image::ImageEncoder};
 use hypervector::types::binary::Binary;
-use hypervector::types::traits::{Accumulator, HyperVector};
+use hypervector::types::traits::HyperVector;
 use hypervector::{
     hdv,
+    metrics::ConfusionMatrix,
     trainer::{argmin, ensemble_fusion, perceptron::PerceptronTrainer},
 };
 use mnist::{self, Image, Mnist, error::MnistError};
-use rand::Rng;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
 use rayon::prelude::*;
@@ -31,198 +31,12 @@ struct Args {
     augment: bool,
 }
 
-const FEATURE_PIXEL_BAG: u8 = 1;
-const FEATURE_HORIZONTAL: u8 = 2;
-const FEATURE_VERTICAL: u8 = 4;
-const FEATURE_DIAGONAL1: u8 = 8;
-const FEATURE_DIAGONAL2: u8 = 16;
-const FEATURE_THINNED: u8 = 32;
-const FEATURE_EDGES: u8 =
-    FEATURE_HORIZONTAL | FEATURE_VERTICAL | FEATURE_DIAGONAL1 | FEATURE_DIAGONAL2;
-
-/// Feature extraction for MNIST digit images using hyperdimensional computing.
-///
-/// Encodes images into high-dimensional binary vectors by combining:
-/// - Pixel bag-of-words (position × intensity)
-/// - Edge features (4 orientations via Sobel operators)
-pub struct MnistEncoder<T: HyperVector> {
-    positions: Vec<T>,             // one for each of 784 pixels (28x28)
-    intensities: ScalarEncoder<T>, // 256 gray levels (0-255)
-    feature_horizontal_edge: T,
-    feature_vertical_edge: T,
-    feature_diag_tl_br: T,
-    feature_diag_tr_bl: T,
-    feature_thinned: T,
-    features: u8,
-}
-
-impl<T: HyperVector> MnistEncoder<T> {
-    pub fn new(mut rng: &mut impl Rng) -> Self {
-        let positions = (0..784).map(|_| T::random(&mut rng)).collect();
-
-        let intensities = ScalarEncoder::<T>::new(0.0, 255.0, 256, rng);
-
-        MnistEncoder {
-            positions,
-            intensities,
-            feature_horizontal_edge: T::random(&mut rng),
-            feature_vertical_edge: T::random(&mut rng),
-            feature_diag_tl_br: T::random(&mut rng),
-            feature_diag_tr_bl: T::random(&mut rng),
-            feature_thinned: T::random(&mut rng),
-            features: 0,
-        }
-    }
-
-    pub fn with_feature_pixel_bag(mut self) -> Self {
-        self.features |= FEATURE_PIXEL_BAG;
-        self
-    }
-
-    pub fn with_feature_horizontal(mut self) -> Self {
-        self.features |= FEATURE_HORIZONTAL;
-        self
-    }
-
-    pub fn with_feature_vertical(mut self) -> Self {
-        self.features |= FEATURE_VERTICAL;
-        self
-    }
-
-    pub fn with_feature_diagonal1(mut self) -> Self {
-        self.features |= FEATURE_DIAGONAL1;
-        self
-    }
-
-    pub fn with_feature_diagonal2(mut self) -> Self {
-        self.features |= FEATURE_DIAGONAL2;
-        self
-    }
-
-    pub fn with_feature_edges(mut self) -> Self {
-        self.features |= FEATURE_EDGES;
-        self
-    }
-
-    pub fn with_feature_thinned(mut self) -> Self {
-        self.features |= FEATURE_THINNED;
-        self
-    }
-
-    fn encode(&self, image: &Image) -> T {
-        let mut accumulator = T::Accumulator::new();
-        const EDGE_THRESHOLD: i16 = 100; // tunable
-        let pixels = image.as_u8_array();
-        let width = image.width();
-        let height = image.height();
-
-        let features = if self.features == 0 {
-            FEATURE_PIXEL_BAG | FEATURE_EDGES
-        } else {
-            self.features
-        };
-
-        if features & FEATURE_PIXEL_BAG != 0 {
-            const THRESHOLD: u8 = 0;
-
-            for (i, &intensity) in pixels.iter().enumerate() {
-                if intensity > THRESHOLD {
-                    let intensity_hdv = self.intensities.encode(intensity as f32);
-                    let pixel_hdv = self.positions[i].bind(intensity_hdv);
-                    let weight = intensity as f64 / 255.0;
-                    accumulator.add(&pixel_hdv, weight);
-                }
-            }
-        }
-
-        // 3x3 Sobel Operator
-        for y in 1..(height - 1) {
-            for x in 1..(width - 1) {
-                // Get intensities of all 9 pixels in the 3x3 grid centered at (x, y)
-                let p00 = pixels[(y - 1) * width + (x - 1)] as i16;
-                let p01 = pixels[(y - 1) * width + x] as i16;
-                let p02 = pixels[(y - 1) * width + (x + 1)] as i16;
-                let p10 = pixels[y * width + (x - 1)] as i16;
-                // p11 is the center pixel
-                let p12 = pixels[y * width + (x + 1)] as i16;
-                let p20 = pixels[(y + 1) * width + (x - 1)] as i16;
-                let p21 = pixels[(y + 1) * width + x] as i16;
-                let p22 = pixels[(y + 1) * width + (x + 1)] as i16;
-                let center_idx = y * width + x;
-
-                const EDGE_WEIGHT: f64 = 4.0;
-                if features & FEATURE_HORIZONTAL != 0 {
-                    // Horizontal Gradient (Sobel Gx): (right side) - (left side)
-                    let diff = (p02 + 2 * p12 + p22) - (p00 + 2 * p10 + p20);
-                    //// --- Check for Horizontal Edge --- (comparing tl and tr)
-                    if diff.abs() > EDGE_THRESHOLD {
-                        let feature_hdv =
-                            self.positions[center_idx].bind(&self.feature_horizontal_edge);
-                        // note - could use use diff instead to discriminate between white->black, black->white,
-                        // but in practise seems worse
-                        let magnitude = (diff.abs() as f64) / 1020.0; // Normalize
-                        accumulator.add(&feature_hdv, EDGE_WEIGHT * magnitude);
-                    }
-                }
-
-                if features & FEATURE_VERTICAL != 0 {
-                    // Vertical Gradient (Sobel Gy): (bottom side) - (top side)
-                    let diff = (p20 + 2 * p21 + p22) - (p00 + 2 * p01 + p02);
-                    //// --- Check for Vertical Edge --- (comparing tl and bl)
-                    if diff.abs() > EDGE_THRESHOLD {
-                        let feature_hdv =
-                            self.positions[center_idx].bind(&self.feature_vertical_edge);
-                        let magnitude = (diff.abs() as f64) / 1020.0; // Normalize
-                        accumulator.add(&feature_hdv, EDGE_WEIGHT * magnitude);
-                    }
-                }
-                if features & FEATURE_DIAGONAL1 != 0 {
-                    // --- Check for Diagonal Edge (/) --- (comparing tr and bl)
-                    // Kernel: [[0, 1, 2], [-1, 0, 1], [-2, -1, 0]]
-                    let diff = (p01 + 2 * p02 + p12) - (p10 + 2 * p20 + p21);
-                    if diff.abs() > EDGE_THRESHOLD {
-                        let feature_hdv = self.positions[center_idx].bind(&self.feature_diag_tl_br);
-                        let magnitude = (diff.abs() as f64) / 1020.0; // Normalize
-                        accumulator.add(&feature_hdv, EDGE_WEIGHT * magnitude);
-                    }
-                }
-
-                if features & FEATURE_DIAGONAL2 != 0 {
-                    //// --- Check for Diagonal Edge (\) --- (comparing tl and br)
-                    // Kernel: [[2, 1, 0], [1, 0, -1], [0, -1, -2]]
-                    let diff = (2 * p00 + p01 + p10) - (p12 + p21 + 2 * p22);
-                    if diff.abs() > EDGE_THRESHOLD {
-                        let feature_hdv = self.positions[center_idx].bind(&self.feature_diag_tr_bl);
-                        let magnitude = (diff.abs() as f64) / 1020.0; // Normalize
-                        accumulator.add(&feature_hdv, EDGE_WEIGHT * magnitude);
-                    }
-                }
-            }
-        }
-
-        if features & FEATURE_THINNED != 0 {
-            // Stack-allocated buffer for the 28x28 MNIST image
-            let mut bits = [false; 784];
-
-            // Binarize - adjustable threshold
-            for (i, &p) in pixels.iter().enumerate() {
-                bits[i] = p > 127;
-            }
-
-            zhang_suen::thinning(&mut bits, width);
-
-            // Bind the positions of the remaining skeleton pixels
-            for (i, &is_skeleton) in bits.iter().enumerate() {
-                if is_skeleton {
-                    let feature_hdv = self.positions[i].bind(&self.feature_thinned);
-                    // Skeleton pixels are binary, so we use a full weight of 1.0
-                    accumulator.add(&feature_hdv, 1.0);
-                }
-            }
-        }
-
-        accumulator.finalize()
-    }
+// row-major pixels of each image, the ImageEncoder input
+fn pixels(images: &[Image]) -> Vec<&[u8]> {
+    images
+        .iter()
+        .map(|im| -> &[u8] { im.as_u8_array() })
+        .collect()
 }
 
 /// Computes the majority vote for a set of predictions.
@@ -258,30 +72,6 @@ pub fn majority_vote(predictions: &[Vec<u8>], num_classes: usize) -> Vec<u8> {
 
 // -- Confusion Matrix ------------------------------------------------------
 
-/// Row = true label, column = predicted label.
-fn confusion_matrix(predictions: &[u8], labels: &[u8]) -> [[u32; 10]; 10] {
-    let mut m = [[0u32; 10]; 10];
-    for (&p, &l) in predictions.iter().zip(labels.iter()) {
-        m[l as usize][p as usize] += 1;
-    }
-    m
-}
-
-fn print_confusion_matrix(m: &[[u32; 10]; 10]) {
-    print!("true\\pred");
-    for j in 0..10 {
-        print!("{j:6}");
-    }
-    println!();
-    for (i, row) in m.iter().enumerate() {
-        print!("{i:8} ");
-        for &count in row {
-            print!("{count:6}");
-        }
-        println!();
-    }
-}
-
 fn main() -> Result<(), MnistError> {
     let args = Args::parse();
 
@@ -307,16 +97,12 @@ fn main() -> Result<(), MnistError> {
         let mut rng = StdRng::seed_from_u64(seed + mn as u64);
         let n_epochs = 2000;
 
-        let encoder = MnistEncoder::<HDV>::new(&mut rng)
-            .with_feature_pixel_bag()
-            .with_feature_edges();
+        let encoder = ImageEncoder::<HDV>::new(28, 28, &mut rng)
+            .with_pixel_bag(0)
+            .with_sobel_edges(100, &mut rng);
 
         println!("Encoding images (Dim {})...", HDV::DIM);
-        let train_hvs: Vec<HDV> = data
-            .train_images
-            .par_iter()
-            .map(|im| encoder.encode(im))
-            .collect();
+        let train_hvs: Vec<HDV> = encoder.encode_batch(&pixels(&data.train_images));
         let mut trainer =
             PerceptronTrainer::<HDV, u8, _, N>::new(&train_hvs, &data.train_labels, None, rng);
 
@@ -336,10 +122,10 @@ fn main() -> Result<(), MnistError> {
         println!();
         let model = trainer.into_model();
 
-        let scores: Vec<[f32; N]> = data
-            .test_images
+        let scores: Vec<[f32; N]> = encoder
+            .encode_batch(&pixels(&data.test_images))
             .par_iter()
-            .map(|im| model.scores(&encoder.encode(im)))
+            .map(|hv| model.distances(hv))
             .collect();
 
         let hard_results: Vec<u8> = scores.iter().map(|s| argmin(s) as u8).collect();
@@ -393,8 +179,9 @@ fn main() -> Result<(), MnistError> {
     println!("Score-fusion Ensemble Accuracy: {fusion_correct:5}/{n_test} = {fusion_acc:.2}%");
 
     println!("\nScore-fusion Confusion Matrix:");
-    let cm = confusion_matrix(&fusion_predictions, &data.test_labels);
-    print_confusion_matrix(&cm);
+    let cm = ConfusionMatrix::from_predictions(&fusion_predictions, &data.test_labels, N);
+    println!("{cm}\n");
+    println!("{}", cm.report::<&str>(&[]));
 
     Ok(())
 }
@@ -13,6 +13,9 @@
 
 use clap::Parser;
 use hypervector::hdv;
+use hypervector::trainer::model_selection::select;
+use hypervector::trainer::multi_label::binary_relevance;
+use hypervector::trainer::perceptron::DynPerceptronTrainer;
 use hypervector::types::binary::Binary;
 use hypervector::types::traits::{Accumulator, HyperVector, UnitAccumulator};
 use mersenne_twister_rs::MersenneTwister64;
@@ -46,6 +49,10 @@ struct Args {
     /// Which split to use (1-5, or 'a' / 'b')
     #[arg(long, default_value = "1")]
     split: String,
+
+    /// Also predict the genres of movies from their HDVs (multi-label)
+    #[arg(long)]
+    genres: bool,
 }
 
 // ── Data loading ─────────────────────────────────────────────────────────────
@@ -143,6 +150,45 @@ fn load_titles(data: &Path) -> (Vec<String>, HashMap<u32, u32>) {
     (titles, id_map)
 }
 
+const GENRES: [&str; 19] = [
+    "unknown",
+    "Action",
+    "Adventure",
+    "Animation",
+    "Children's",
+    "Comedy",
+    "Crime",
+    "Documentary",
+    "Drama",
+    "Fantasy",
+    "Film-Noir",
+    "Horror",
+    "Musical",
+    "Mystery",
+    "Romance",
+    "Sci-Fi",
+    "Thriller",
+    "War",
+    "Western",
+];
+
+/// Genres of every movie - the 0/1 flags that end a line of u.item
+fn load_genres(data: &Path) -> Vec<Vec<usize>> {
+    let bytes = fs::read(data.join("u.item")).unwrap_or_default();
+    let text: String = bytes.iter().map(|&b| b as char).collect();
+    text.lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split('|').collect();
+            fields[fields.len().saturating_sub(GENRES.len())..]
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| **f == "1")
+                .map(|(g, _)| g)
+                .collect()
+        })
+        .collect()
+}
+
 // ── Evaluation helpers ────────────────────────────────────────────────────────
 
 fn print_metrics(
@@ -469,9 +515,30 @@ fn demo_user<H: HyperVector + Sync>(
     }
 }
 
+/// Multi-label genre prediction from the collaborative movie HDVs: a perceptron
+/// per genre (binary relevance) trained on 3 of 5 movies, with thresholds tuned on
+/// one and tested on the last.
+fn predict_genres<H: HyperVector + Send + Sync>(item_hdvs: &[H], genres: &[Vec<usize>]) {
+    let part = |r: usize| -> Vec<usize> { (0..item_hdvs.len()).filter(|i| i % 5 == r).collect() };
+    let train: Vec<usize> = (0..item_hdvs.len()).filter(|i| i % 5 >= 2).collect();
+    let (tune, test) = (part(1), part(0));
+
+    let train_hdvs = select(item_hdvs, &train);
+    let train_genres = select(genres, &train);
+    let mut model = binary_relevance(&train_hdvs, &train_genres, GENRES.len(), |g, hdvs, has| {
+        let rng = MersenneTwister64::new(g as u64);
+        DynPerceptronTrainer::new(hdvs, has, None, 2, rng).fit(50).0
+    });
+    model.tune_thresholds(&select(item_hdvs, &tune), &select(genres, &tune));
+
+    let metrics = model.evaluate(&select(item_hdvs, &test), &select(genres, &test));
+    println!("Genre prediction, {} test movies:", test.len());
+    println!("{}\n", metrics.report(&GENRES));
+}
+
 // ── Main ─────────────────────────────────────────────────────────────────────
 
-fn run<H: HyperVector + Sync>(args: &Args) {
+fn run<H: HyperVector + Send + Sync>(args: &Args) {
     let split = &args.split;
     let (titles, id_map) = load_titles(&args.data);
     let train = Ratings::load(&args.data.join(format!("u{split}.base")), &id_map);
@@ -492,6 +559,10 @@ fn run<H: HyperVector + Sync>(args: &Args) {
 
     evaluate(&train, &test, &item_hdvs, args);
     demo_user(1, &train, &item_hdvs, &titles, args);
+
+    if args.genres {
+        predict_genres(&item_hdvs, &load_genres(&args.data));
+    }
 }
 
 fn main() {
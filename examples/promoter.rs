@@ -1,5 +1,7 @@
 use clap::Parser;
 use hypervector::hdv;
+use hypervector::trainer::DynPrototypeModel;
+use hypervector::trainer::model_selection::{CvReport, cross_validate, leave_one_out};
 use hypervector::types::traits::{HyperVector, UnitAccumulator};
 use hypervector::types::{binary::Binary, modular::Modular};
 use mersenne_twister_rs::MersenneTwister64;
@@ -169,71 +171,43 @@ fn encode_sequence<H: HyperVector>(seq: &[u8], alphabet: &Alphabet<H>, n: usize)
     acc.finalize()
 }
 
-fn train<H: HyperVector>(
-    data_plus: &[H],
-    data_minus: &[H],
-    skip_plus: Option<usize>,
-    skip_minus: Option<usize>,
-) -> (H, H) {
-    let mut acc = <H as HyperVector>::UnitAccumulator::default();
-    for (i, h) in data_plus.iter().enumerate() {
-        if skip_plus != Some(i) {
-            acc.add(h);
-        }
+// one prototype per class: the bundle of its training samples
+fn train<H: HyperVector>(samples: &[H], labels: &[usize]) -> DynPrototypeModel<H> {
+    let mut accs = [
+        <H as HyperVector>::UnitAccumulator::default(),
+        <H as HyperVector>::UnitAccumulator::default(),
+    ];
+    for (h, &label) in samples.iter().zip(labels) {
+        accs[label].add(h);
     }
-    let plus = acc.finalize();
-
-    let mut acc = <H as HyperVector>::UnitAccumulator::default();
-    for (i, h) in data_minus.iter().enumerate() {
-        if skip_minus != Some(i) {
-            acc.add(h);
-        }
+    DynPrototypeModel {
+        prototypes: accs.iter_mut().map(|acc| acc.finalize()).collect(),
     }
-    (plus, acc.finalize())
 }
 
-fn run_loo<H: HyperVector>(alphabet: &Alphabet<H>, n: usize) -> (usize, usize) {
-    let plus_encoded: Vec<H> = DATA_PLUS
+// leave one out cross-validation - label 0 promoter, 1 non-promoter
+fn run_loo<H: HyperVector + Send + Sync>(alphabet: &Alphabet<H>, n: usize) -> CvReport {
+    let samples: Vec<H> = DATA_PLUS
         .iter()
+        .chain(DATA_MINUS.iter())
         .map(|seq| encode_sequence(seq, alphabet, n))
         .collect();
-    let minus_encoded: Vec<H> = DATA_MINUS
-        .iter()
-        .map(|seq| encode_sequence(seq, alphabet, n))
+    let labels: Vec<usize> = (0..samples.len())
+        .map(|i| usize::from(i >= DATA_PLUS.len()))
         .collect();
 
-    let mut correct = 0;
-    let mut total = 0;
-
-    // PLUS samples
-    for i in 0..DATA_PLUS.len() {
-        let (plus_hv, minus_hv) = train(&plus_encoded, &minus_encoded, Some(i), None);
-
-        let h = &plus_encoded[i];
-        if h.distance(&plus_hv) < h.distance(&minus_hv) {
-            correct += 1;
-        }
-        total += 1;
-    }
-
-    // MINUS samples
-    for i in 0..DATA_MINUS.len() {
-        let (plus_hv, minus_hv) = train(&plus_encoded, &minus_encoded, None, Some(i));
-
-        let h = &minus_encoded[i];
-        if h.distance(&plus_hv) > h.distance(&minus_hv) {
-            correct += 1;
-        }
-        total += 1;
-    }
-
-    (correct, total)
+    let folds = leave_one_out(samples.len());
+    cross_validate(&samples, &labels, &folds, |_, samples, labels| {
+        train(samples, labels)
+    })
 }
 
-fn run_suite<H: HyperVector>(alphabet: &Alphabet<H>, args: &Args) {
+fn run_suite<H: HyperVector + Send + Sync>(alphabet: &Alphabet<H>, args: &Args) {
     for n in 2..=args.ngram {
-        let (correct, total) = run_loo(alphabet, n);
-        let acc = 100.0 * correct as f64 / total as f64;
+        let report = run_loo(alphabet, n);
+        let correct: usize = report.folds.iter().map(|f| f.correct).sum();
+        let total = report.folds.len();
+        let acc = 100.0 * report.pooled_accuracy();
         println!("{n}-gram: Accuracy: {acc:.2}% ({correct}/{total})");
     }
 }
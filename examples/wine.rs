@@ -1,17 +1,18 @@
 use std::fmt;
-use std::io::{self, Write};
+use std::io;
 
 use clap::{Parser, ValueEnum};
 use rand::Rng;
 use rayon::prelude::*;
 
-use hypervector::datasets::wine_dataset::{Dataset, Label, NUM_CLASSES};
-use hypervector::encoding::TabularEncoder;
+use hypervector::datasets::wine_dataset::{Dataset, Label, N_FEATURES, NUM_CLASSES};
+use hypervector::encoding::{ScalarFit, TabularEncoder};
 use hypervector::hdv;
+use hypervector::metrics::{ConfusionMatrix, RegressionMetrics};
 use hypervector::trainer::{
-    Classifier, Trainer, ensemble_accuracy, lvq::LvqTrainer,
-    multi_perceptron::PerceptronMultiTrainer, pa::PaTrainer, pa::PaVariant,
-    perceptron::PerceptronTrainer,
+    Classifier, Schedule, driver::Driver, ensemble_accuracy, ensemble_vote, lvq::LvqTrainer,
+    model_selection::Summary, multi_perceptron::PerceptronMultiTrainer, pa::PaTrainer,
+    pa::PaVariant, perceptron::PerceptronTrainer, regression::RegHdTrainer, regression::Regressor,
 };
 use hypervector::types::traits::HyperVector;
 use hypervector::types::{binary::Binary, complex::ComplexHDV, modular::Modular, real::RealHDV};
@@ -40,6 +41,25 @@ impl fmt::Display for TrainerKind {
     }
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum SchemaKind {
+    Fixed,      // hand-picked feature ranges
+    Minmax,     // ranges fitted to the training set
+    Percentile, // ranges clipped at the 1st and 99th percentile of the training set
+    Quantile,   // equal-frequency levels fitted to the training set
+}
+
+impl fmt::Display for SchemaKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaKind::Fixed => write!(f, "fixed"),
+            SchemaKind::Minmax => write!(f, "minmax"),
+            SchemaKind::Percentile => write!(f, "percentile"),
+            SchemaKind::Quantile => write!(f, "quantile"),
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -57,6 +77,10 @@ struct Args {
     #[arg(long, default_value_t = TrainerKind::Perceptron)]
     trainer: TrainerKind,
 
+    #[arg(long, default_value_t = SchemaKind::Fixed)]
+    /// how feature ranges are chosen
+    schema: SchemaKind,
+
     #[arg(long, default_value_t = 1)]
     /// number of prototypes per class
     prototypes: usize,
@@ -70,6 +94,18 @@ struct Args {
 
     #[arg(long, default_value_t = 9)]
     ensemble_size: usize,
+
+    #[arg(long)]
+    /// mini-batch size of the pa trainers - online training if not given
+    batch_size: Option<usize>,
+
+    #[arg(long)]
+    /// predict the quality rating with RegHD regression instead of classifying
+    regression: bool,
+
+    #[arg(long, default_value_t = 1)]
+    /// number of RegHD models
+    models: usize,
 }
 
 fn valid_dim(s: &str) -> Result<usize, String> {
@@ -96,33 +132,11 @@ hdv!(real, RealHDV1024, 1024);
 hdv!(real, RealHDV2048, 2048);
 hdv!(complex, ComplexHDV1024, 1024);
 
-fn train<T, Tr>(mut trainer: Tr, epochs: usize) -> Tr::Model
-where
-    T: HyperVector,
-    Tr: Trainer<T>,
-{
-    for epoch in 1..=epochs {
-        let r = trainer.step(epoch);
-        print!(
-            "Epoch {epoch}: Training Accuracy {}/{}={:.2}%\r",
-            r.correct,
-            r.total(),
-            r.accuracy() * 100.0
-        );
-        std::io::stdout().flush().unwrap();
-        if r.errors == 0 {
-            break;
-        }
-    }
-    println!();
-    trainer.into_model()
-}
-
-fn run<T: HyperVector + Sync + Send>(
+fn encode<T: HyperVector + Sync + Send>(
     data: &Dataset,
     rng: &mut impl Rng,
     args: &Args,
-) -> Vec<usize> {
+) -> io::Result<(Vec<T>, Vec<T>)> {
     const LEVELS: usize = 64;
     let schema: &[(f32, f32, usize)] = &[
         (4.0, 16.0, LEVELS),
@@ -166,14 +180,60 @@ fn run<T: HyperVector + Sync + Send>(
     // sulphates              0.22000    1.08000
     // alcohol                8.00000   14.20000
 
-    let encoder = TabularEncoder::<T>::new(schema, rng);
-    let train_hvs: Vec<T> = data.train.par_iter().map(|s| encoder.encode(s)).collect();
-    let test_hvs: Vec<T> = data.test.par_iter().map(|s| encoder.encode(s)).collect();
+    let fit = match args.schema {
+        SchemaKind::Fixed => None,
+        SchemaKind::Minmax => Some(ScalarFit::new(LEVELS)),
+        SchemaKind::Percentile => Some(ScalarFit::new(LEVELS).with_percentiles(1.0, 99.0)),
+        SchemaKind::Quantile => Some(ScalarFit::new(LEVELS).with_quantiles()),
+    };
+    let encoder = match fit {
+        None => TabularEncoder::<T>::new(schema, rng),
+        Some(fit) => {
+            let encoder = TabularEncoder::<T>::fit(&data.train, &[fit; N_FEATURES], rng)?;
+            println!("Fitted schema ({}):\n{encoder}", args.schema);
+            encoder
+        }
+    };
+    let train_hvs: Vec<T> = data
+        .train
+        .par_iter()
+        .map(|s| encoder.encode_values(s))
+        .collect();
+    let test_hvs: Vec<T> = data
+        .test
+        .par_iter()
+        .map(|s| encoder.encode_values(s))
+        .collect();
+    Ok((train_hvs, test_hvs))
+}
+
+/// Predicted quality ratings of the test set
+fn regress<T: HyperVector + Sync + Send>(
+    data: &Dataset,
+    rng: &mut impl Rng,
+    args: &Args,
+) -> io::Result<Vec<f32>> {
+    let (train_hvs, test_hvs) = encode::<T>(data, rng, args)?;
+    let trainer = RegHdTrainer::new(&train_hvs, &data.train_quality, args.models, rng)
+        .with_schedule(Schedule::InvSqrt(0.1));
+    let (model, history) = trainer.fit(args.epochs);
+    if let Some(last) = history.last() {
+        println!("Epoch {}: training {last}", history.len());
+    }
+    Ok(model.predict_all(&test_hvs))
+}
+
+fn run<T: HyperVector + Sync + Send>(
+    data: &Dataset,
+    rng: &mut impl Rng,
+    args: &Args,
+) -> io::Result<Vec<usize>> {
+    let (train_hvs, test_hvs) = encode::<T>(data, rng, args)?;
 
     let k = args.prototypes;
     let epochs = args.epochs;
 
-    match args.trainer {
+    let preds = match args.trainer {
         TrainerKind::Perceptron => {
             let trainer = PerceptronTrainer::<T, Label, _, NUM_CLASSES>::new(
                 &train_hvs,
@@ -181,7 +241,11 @@ fn run<T: HyperVector + Sync + Send>(
                 None,
                 &mut *rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Pa => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -190,8 +254,13 @@ fn run<T: HyperVector + Sync + Send>(
                 None,
                 PaVariant::Pa,
                 &mut *rng,
-            );
-            train(trainer, epochs).classify_all(&test_hvs)
+            )
+            .with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Pai => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -200,8 +269,13 @@ fn run<T: HyperVector + Sync + Send>(
                 None,
                 PaVariant::PaI { c: 0.1 },
                 rng,
-            );
-            train(trainer, epochs).classify_all(&test_hvs)
+            )
+            .with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Paii => {
             let trainer = PaTrainer::<T, Label, _, NUM_CLASSES>::new(
@@ -210,8 +284,13 @@ fn run<T: HyperVector + Sync + Send>(
                 None,
                 PaVariant::PaII { c: 1.0 },
                 rng,
-            );
-            train(trainer, epochs).classify_all(&test_hvs)
+            )
+            .with_batch_size(args.batch_size);
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Multi => {
             let trainer = PerceptronMultiTrainer::<T, _>::new(
@@ -222,7 +301,11 @@ fn run<T: HyperVector + Sync + Send>(
                 k,
                 rng,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
         TrainerKind::Lvq => {
             let trainer = LvqTrainer::<T, _>::new(
@@ -234,44 +317,45 @@ fn run<T: HyperVector + Sync + Send>(
                 rng,
                 args.window,
             );
-            train(trainer, epochs).classify_all(&test_hvs)
-        }
-    }
-}
-
-fn stats(v: &[f64]) -> Option<(f64, f64, f64)> {
-    if v.is_empty() {
-        return None;
-    }
-
-    let mut min = v[0];
-    let mut max = v[0];
-    let mut sum = v[0];
-
-    for &x in &v[1..] {
-        if x < min {
-            min = x;
+            Driver::new(epochs)
+                .with_progress()
+                .run(trainer)
+                .model
+                .classify_all(&test_hvs)
         }
-        if x > max {
-            max = x;
-        }
-        sum += x;
-    }
-
-    let avg = sum / v.len() as f64;
-    Some((100.0 * min, 100.0 * max, 100.0 * avg))
+    };
+    Ok(preds)
 }
 
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
     let ensemble_size = args.ensemble_size;
-    let dataset = if args.colour.as_str() == "red" {
-        Dataset::load("DATA/WINEQUALITY_RED")?
+    let dir = if args.colour.as_str() == "red" {
+        "DATA/WINEQUALITY_RED"
     } else {
-        Dataset::load("DATA/WINEQUALITY_WHITE")?
+        "DATA/WINEQUALITY_WHITE"
     };
     let mut rng = MersenneTwister64::default();
 
+    if args.regression {
+        // the files in DATA hold 0/1 labels - regression needs the ratings
+        let dir = format!("{dir}_RAW");
+        let dataset = Dataset::load(&dir).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                io::Error::new(e.kind(), format!("{dir}: {e}, see READMEwine.md"))
+            }
+            _ => e,
+        })?;
+        if !dataset.has_ratings() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no 0-10 quality ratings in {dir}, see READMEwine.md"),
+            ));
+        }
+        return run_regression(&dataset, &mut rng, &args);
+    }
+    let dataset = Dataset::load(dir)?;
+
     let mut all_predictions = Vec::with_capacity(ensemble_size);
     let mut accs = Vec::with_capacity(ensemble_size);
     for i in 1..=ensemble_size {
@@ -296,7 +380,7 @@ fn main() -> Result<(), io::Error> {
                 );
                 return Ok(());
             }
-        };
+        }?;
         let (correct, errors, acc) = ensemble_accuracy(
             std::slice::from_ref(&preds),
             &dataset.test_labels,
@@ -322,9 +406,71 @@ fn main() -> Result<(), io::Error> {
         println!();
     }
 
-    if let Some((min, max, avg)) = stats(&accs) {
-        println!("Model accuracies - avg {avg:.2}%, min {min:.2}%, max {max:.2}")
+    if !all_predictions.is_empty() {
+        let votes = ensemble_vote::<Label>(&all_predictions, NUM_CLASSES);
+        let cm = ConfusionMatrix::from_predictions(&votes, &dataset.test_labels, NUM_CLASSES);
+        println!(
+            "Ensemble of {ensemble_size}:\n{}\n",
+            cm.report(&["bad", "good"])
+        );
     }
+    if let Some(summary) = Summary::new(&accs) {
+        println!("Model accuracies - {summary}")
+    }
+
+    Ok(())
+}
+
+fn run_regression(dataset: &Dataset, rng: &mut impl Rng, args: &Args) -> Result<(), io::Error> {
+    let ensemble_size = args.ensemble_size;
+    let targets = &dataset.test_quality;
+    let mut sum = vec![0.0; targets.len()];
+    let mut rmses = Vec::with_capacity(ensemble_size);
+    for i in 1..=ensemble_size {
+        let preds = match (args.mode.as_str(), args.dim) {
+            ("binary", 1024) => regress::<BinaryHDV1024>(dataset, rng, args),
+            ("binary", 2048) => regress::<BinaryHDV2048>(dataset, rng, args),
+            ("binary", 4096) => regress::<BinaryHDV4096>(dataset, rng, args),
+            ("binary", 8192) => regress::<BinaryHDV8192>(dataset, rng, args),
+            ("binary", 16384) => regress::<BinaryHDV16384>(dataset, rng, args),
+            ("modular", 1024) => regress::<ModularHDV1024>(dataset, rng, args),
+            ("modular", 2048) => regress::<ModularHDV2048>(dataset, rng, args),
+            ("modular", 4096) => regress::<ModularHDV4096>(dataset, rng, args),
+            ("modular", 8192) => regress::<ModularHDV8192>(dataset, rng, args),
+            ("modular", 16384) => regress::<ModularHDV16384>(dataset, rng, args),
+            ("real", 1024) => regress::<RealHDV1024>(dataset, rng, args),
+            ("real", 2048) => regress::<RealHDV2048>(dataset, rng, args),
+            ("complex", 1024) => regress::<ComplexHDV1024>(dataset, rng, args),
+            _ => {
+                eprintln!(
+                    "Unsupported combination: mode={} dim={}",
+                    args.mode, args.dim
+                );
+                return Ok(());
+            }
+        }?;
+        let metrics = RegressionMetrics::new(&preds, targets);
+        println!("Model {i}/{ensemble_size} - test: {metrics}");
+        rmses.push(metrics.rmse);
 
+        // the ensemble predicts the mean of its models
+        for (s, p) in sum.iter_mut().zip(&preds) {
+            *s += p;
+        }
+        if i > 2 {
+            let mean: Vec<f32> = sum.iter().map(|s| s / i as f32).collect();
+            println!(
+                "Ensemble of {i} - test: {}",
+                RegressionMetrics::new(&mean, targets)
+            );
+        }
+        println!();
+    }
+    if let Some(s) = Summary::new(&rmses) {
+        println!(
+            "Model RMSE - avg {:.4} ± {:.4}, min {:.4}, max {:.4}",
+            s.mean, s.std, s.min, s.max
+        );
+    }
     Ok(())
 }
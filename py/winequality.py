@@ -38,6 +38,7 @@ class WineBaseline:
     def load_and_preprocess(self):
         """Loads data, creates binary target, and scales features."""
         self.df = pd.read_csv(self.data_path)
+        ratings = self.df["quality"].copy()
 
         # 1. Simplify target: 3-5 -> 'bad' (0), 6-8 -> 'good' (1)
         self.df["quality"] = self.df["quality"].apply(lambda x: 0 if x <= 5 else 1)
@@ -59,6 +60,12 @@ class WineBaseline:
             test_df.to_csv("wine_test.csv", index=False)
             print("Exported wine_train.csv and wine_test.csv")
 
+            # The same split with the 0-10 ratings, for regression
+            for name, X in [("train", self.X_train), ("test", self.X_test)]:
+                raw_df = pd.concat([X, ratings.loc[X.index]], axis=1)
+                raw_df.to_csv(f"wine_{name}_raw.csv", index=False)
+            print("Exported wine_train_raw.csv and wine_test_raw.csv")
+
             # Compute min/max for each feature (TRAIN ONLY)
             feature_stats = pd.DataFrame({
                     "min": self.X_train.min(),
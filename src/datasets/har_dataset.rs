@@ -23,6 +23,10 @@ use std::str::FromStr;
 // y_test.txt and y_train.txt
 // activity (1-6), line n refers tofeature v
 // activity (1-6) one per line, activity on line n refers to feature vector on line n in X_test/X_train/y_test/y_train
+//
+// Inertial Signals/{body_acc,body_gyro,total_acc}_{x,y,z}_{train,test}.txt
+// raw sensor readings, one 2.56 s window (128 readings at 50 Hz) per line - line n is
+// the same window as line n in X_train/X_test
 
 pub const NUM_CLASSES: usize = 6;
 pub const N_FEATURES: usize = 561;
@@ -109,6 +113,122 @@ fn load_subjects(path: &Path) -> io::Result<Vec<u8>> {
         .collect()
 }
 
+// ── Raw inertial signals ──────────────────────────────────────────────────────
+
+pub const N_CHANNELS: usize = 9;
+pub const N_TIMESTEPS: usize = 128;
+pub const CHANNELS: [&str; N_CHANNELS] = [
+    "body_acc_x",
+    "body_acc_y",
+    "body_acc_z",
+    "body_gyro_x",
+    "body_gyro_y",
+    "body_gyro_z",
+    "total_acc_x",
+    "total_acc_y",
+    "total_acc_z",
+];
+/// N_TIMESTEPS readings of the N_CHANNELS signals
+pub type Recording = Vec<[f32; N_CHANNELS]>;
+
+pub struct InertialDataset {
+    pub train: Vec<Recording>,
+    pub test: Vec<Recording>,
+    pub train_labels: Vec<Label>,
+    pub test_labels: Vec<Label>,
+    pub train_subjects: Vec<u8>,
+    pub test_subjects: Vec<u8>,
+}
+
+impl InertialDataset {
+    pub fn load(dir: &str) -> io::Result<Self> {
+        let base = Path::new(dir);
+        let dataset = Self {
+            train: load_recordings(base, "train")?,
+            test: load_recordings(base, "test")?,
+            train_labels: load_labels(&base.join("train/y_train.txt"))?,
+            test_labels: load_labels(&base.join("test/y_test.txt"))?,
+            train_subjects: load_subjects(&base.join("train/subject_train.txt"))?,
+            test_subjects: load_subjects(&base.join("test/subject_test.txt"))?,
+        };
+        let train = (
+            &dataset.train,
+            &dataset.train_labels,
+            &dataset.train_subjects,
+        );
+        let test = (&dataset.test, &dataset.test_labels, &dataset.test_subjects);
+        for (split, (recordings, labels, subjects)) in [("train", train), ("test", test)] {
+            if labels.len() != recordings.len() || subjects.len() != recordings.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{split}: {} windows, {} labels, {} subjects",
+                        recordings.len(),
+                        labels.len(),
+                        subjects.len()
+                    ),
+                ));
+            }
+        }
+        Ok(dataset)
+    }
+}
+
+fn load_recordings(base: &Path, split: &str) -> io::Result<Vec<Recording>> {
+    let mut recordings: Vec<Recording> = Vec::new();
+    for (c, channel) in CHANNELS.iter().enumerate() {
+        let path = base.join(format!("{split}/Inertial Signals/{channel}_{split}.txt"));
+        let file = fs::File::open(&path)?;
+        let mut rows = 0;
+        for (n, line) in io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            rows += 1;
+            if c == 0 {
+                recordings.push(vec![[0.0; N_CHANNELS]; N_TIMESTEPS]);
+            }
+            let recording = recordings.get_mut(n).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: more windows than {}", path.display(), CHANNELS[0]),
+                )
+            })?;
+            let mut tokens = line.split_whitespace();
+            for (t, step) in recording.iter_mut().enumerate() {
+                let tok = tokens.next().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("expected {N_TIMESTEPS} readings, got {t}"),
+                    )
+                })?;
+                step[c] = f32::from_str(tok)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            }
+            if tokens.next().is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}: line {}: more than {N_TIMESTEPS} readings",
+                        path.display(),
+                        n + 1
+                    ),
+                ));
+            }
+        }
+        if rows != recordings.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: {rows} windows, {} has {}",
+                    path.display(),
+                    CHANNELS[0],
+                    recordings.len()
+                ),
+            ));
+        }
+    }
+    Ok(recordings)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Label {
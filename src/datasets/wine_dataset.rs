@@ -12,6 +12,10 @@ pub struct Dataset {
     pub test: Vec<Sample>,
     pub train_labels: Vec<Label>,
     pub test_labels: Vec<Label>,
+    /// The quality column as is - the 0..=10 rating of the original data, or 0/1
+    /// where it was mapped to Bad/Good already
+    pub train_quality: Vec<f32>,
+    pub test_quality: Vec<f32>,
 }
 
 impl Dataset {
@@ -19,21 +23,29 @@ impl Dataset {
         let base = Path::new(dir);
         let fname1 = "winequality_train.csv";
         let fname2 = "winequality_test.csv";
-        let (train, train_labels) = load_samples(&base.join(fname1))?;
-        let (test, test_labels) = load_samples(&base.join(fname2))?;
+        let (train, train_labels, train_quality) = load_samples(&base.join(fname1))?;
+        let (test, test_labels, test_quality) = load_samples(&base.join(fname2))?;
         Ok(Self {
             train,
             test,
             train_labels,
             test_labels,
+            train_quality,
+            test_quality,
         })
     }
+
+    /// Whether the quality column holds the 0-10 ratings rather than 0/1 labels
+    pub fn has_ratings(&self) -> bool {
+        self.train_quality.iter().any(|&q| q > 1.0)
+    }
 }
 
-fn load_samples(path: &Path) -> io::Result<(Vec<Sample>, Vec<Label>)> {
+fn load_samples(path: &Path) -> io::Result<(Vec<Sample>, Vec<Label>, Vec<f32>)> {
     let file = fs::File::open(path)?;
     let mut samples = Vec::new();
     let mut labels = Vec::new();
+    let mut quality = Vec::new();
 
     for line in io::BufReader::new(file).lines().skip(1) {
         let line = line?;
@@ -55,13 +67,18 @@ fn load_samples(path: &Path) -> io::Result<(Vec<Sample>, Vec<Label>)> {
 
         let label_f: u8 = u8::from_str(tokens[N_FEATURES])
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        let label = Label::from_label(label_f);
+        let label = if label_f <= 1 {
+            Label::from_label(label_f)
+        } else {
+            Label::from_rating(label_f)
+        };
 
         samples.push(sample);
         labels.push(label);
+        quality.push(label_f as f32);
     }
 
-    Ok((samples, labels))
+    Ok((samples, labels, quality))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -79,6 +96,11 @@ impl Label {
             _ => panic!("Invalid class label: {n}"),
         }
     }
+
+    /// Bad for ratings up to 5, Good above
+    pub fn from_rating(rating: u8) -> Self {
+        if rating <= 5 { Label::Bad } else { Label::Good }
+    }
 }
 
 impl From<Label> for usize {
@@ -1,11 +1,119 @@
 use crate::types::binary::Binary;
 use crate::types::complex::ComplexHDV;
-use crate::types::modular::Modular;
+use crate::types::modular::{Modular, quantize_phase};
 use crate::types::real::RealHDV;
 use crate::types::traits::{Accumulator, HyperVector};
 use fwht::fwht;
+use mersenne_twister_rs::MersenneTwister64;
 use rand::seq::SliceRandom;
 use rand::{Rng, RngExt};
+use rand_distr::{Cauchy, Distribution, Normal};
+use rayon::prelude::*;
+use rustfft::num_complex::Complex;
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hasher;
+use std::io;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+pub mod image;
+pub mod timeseries;
+
+// ── Encoder ─────────────────────────────────────────────────────────────────────────
+//
+// Common interface of the encoders below, so that batch encoding, trainers and tools
+// can be written once. The encoders keep their inherent `encode` methods - some of
+// them return a reference into a codebook, the trait always returns an owned vector.
+
+pub trait Encoder<Input: ?Sized, H: HyperVector> {
+    fn encode(&self, input: &Input) -> H;
+
+    /// Encodes the inputs in parallel
+    fn encode_batch<I>(&self, inputs: &[I]) -> Vec<H>
+    where
+        I: Borrow<Input> + Sync,
+        H: Send,
+        Self: Sized + Sync,
+    {
+        inputs
+            .par_iter()
+            .map(|input| self.encode(input.borrow()))
+            .collect()
+    }
+
+    /// Binds every encoding with `key`, e.g. a field or role vector
+    fn bound_to(self, key: H) -> Bound<Self, H>
+    where
+        Self: Sized,
+    {
+        Bound { encoder: self, key }
+    }
+
+    /// Permutes every encoding by `by`, e.g. a sequence position
+    fn permuted(self, by: usize) -> Permuted<Self>
+    where
+        Self: Sized,
+    {
+        Permuted { encoder: self, by }
+    }
+}
+
+pub struct Bound<E, H> {
+    pub encoder: E,
+    pub key: H,
+}
+
+impl<Input: ?Sized, H: HyperVector, E: Encoder<Input, H>> Encoder<Input, H> for Bound<E, H> {
+    fn encode(&self, input: &Input) -> H {
+        self.encoder.encode(input).bind(&self.key)
+    }
+}
+
+pub struct Permuted<E> {
+    pub encoder: E,
+    pub by: usize,
+}
+
+impl<Input: ?Sized, H: HyperVector, E: Encoder<Input, H>> Encoder<Input, H> for Permuted<E> {
+    fn encode(&self, input: &Input) -> H {
+        self.encoder.encode(input).permute(self.by)
+    }
+}
+
+/// Weighted bundle of sub-encoders that all see the same input, e.g. different
+/// views of an image, or fields of a record (combined with `bound_to`).
+pub struct WeightedBundle<'a, Input: ?Sized, H: HyperVector> {
+    pub parts: Vec<(Box<dyn Encoder<Input, H> + Send + Sync + 'a>, f64)>,
+}
+
+impl<'a, Input: ?Sized, H: HyperVector> WeightedBundle<'a, Input, H> {
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    pub fn with(mut self, encoder: impl Encoder<Input, H> + Send + Sync + 'a, weight: f64) -> Self {
+        self.parts.push((Box::new(encoder), weight));
+        self
+    }
+}
+
+impl<Input: ?Sized, H: HyperVector> Default for WeightedBundle<'_, Input, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Input: ?Sized, H: HyperVector> Encoder<Input, H> for WeightedBundle<'_, Input, H> {
+    fn encode(&self, input: &Input) -> H {
+        let mut acc = H::Accumulator::new();
+        for (encoder, weight) in &self.parts {
+            acc.add(&encoder.encode(input), *weight);
+        }
+        acc.finalize()
+    }
+}
 
 // ── ScalarEncoder ───────────────────────────────────────────────────────────────────
 //
@@ -15,12 +123,272 @@ pub struct ScalarEncoder<H: HyperVector> {
     pub min: f32,
     pub max: f32,
     pub basis: Vec<H>,
+    pub transform: Transform,
+    // level thresholds (transformed units) - empty means equal-width levels over min..max
+    pub boundaries: Vec<f32>,
 }
 
 impl<H: HyperVector> ScalarEncoder<H> {
     // calculate `num_levels` correlated basis vectors representing the numbers min..max
     // correlated means 1st vector is more similar to 2nd than to 3rd etc.
     pub fn new<R: Rng>(min: f32, max: f32, num_levels: usize, rng: &mut R) -> Self {
+        let levels = InterpolatingEncoder::new(min, max, rng);
+        let basis = (0..num_levels)
+            .map(|i| levels.at_fraction(i as f32 / (num_levels - 1) as f32))
+            .collect();
+
+        Self {
+            min,
+            max,
+            basis,
+            transform: Transform::Identity,
+            boundaries: Vec::new(),
+        }
+    }
+
+    pub fn from_spec<R: Rng>(spec: &ScalarSpec, rng: &mut R) -> Self {
+        let mut encoder = Self::new(spec.min, spec.max, spec.levels, rng);
+        encoder.transform = spec.transform;
+        encoder.boundaries = spec.boundaries.clone();
+        encoder
+    }
+
+    /// Pick range and levels from (training) values, see ScalarFit
+    pub fn fit<R: Rng>(values: &[f32], fit: &ScalarFit, rng: &mut R) -> Self {
+        Self::from_spec(&fit.fit(values), rng)
+    }
+
+    /// The parameters this encoder was built from - can be reported and reused.
+    pub fn spec(&self) -> ScalarSpec {
+        ScalarSpec {
+            min: self.min,
+            max: self.max,
+            levels: self.basis.len(),
+            transform: self.transform,
+            boundaries: self.boundaries.clone(),
+        }
+    }
+
+    pub fn level(&self, value: f32) -> usize {
+        let x = self.transform.apply(value);
+        if self.boundaries.is_empty() {
+            let normalized = ((x - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+            (normalized * (self.basis.len() - 1) as f32) as usize
+        } else {
+            self.boundaries.partition_point(|&b| b <= x)
+        }
+    }
+
+    pub fn encode(&self, value: f32) -> &H {
+        &self.basis[self.level(value)]
+    }
+}
+
+impl<H: HyperVector> Encoder<f32, H> for ScalarEncoder<H> {
+    fn encode(&self, value: &f32) -> H {
+        ScalarEncoder::encode(self, *value).clone()
+    }
+}
+
+// ── Fitting ─────────────────────────────────────────────────────────────────────────
+//
+// Data-driven choice of ScalarEncoder parameters. A ScalarFit describes *how* to pick
+// the parameters, fitting it to training values gives a ScalarSpec - the resolved
+// parameters, which can be printed, stored and reused on test data.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    Identity,
+    /// ln(1 + x) - compresses skewed, non-negative columns such as monetary amounts
+    Log1p,
+}
+
+impl Transform {
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Transform::Identity => x,
+            Transform::Log1p => x.ln_1p(),
+        }
+    }
+
+    /// Back to raw units
+    #[inline]
+    pub fn invert(self, x: f32) -> f32 {
+        match self {
+            Transform::Identity => x,
+            Transform::Log1p => x.exp_m1(),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Transform::Identity => write!(f, "identity"),
+            Transform::Log1p => write!(f, "log1p"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeFit {
+    /// Given range, in raw (untransformed) units
+    Fixed { min: f32, max: f32 },
+    /// Smallest and largest value seen
+    MinMax,
+    /// Clip at the `low` and `high` percentiles (0-100) - robust to outliers
+    Percentile { low: f32, high: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binning {
+    /// Levels of equal width
+    Uniform,
+    /// Levels of (approximately) equal frequency
+    Quantile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalarFit {
+    pub levels: usize,
+    pub range: RangeFit,
+    pub binning: Binning,
+    pub transform: Transform,
+}
+
+impl ScalarFit {
+    /// min/max range, equal-width levels, no transform
+    pub fn new(levels: usize) -> Self {
+        Self {
+            levels,
+            range: RangeFit::MinMax,
+            binning: Binning::Uniform,
+            transform: Transform::Identity,
+        }
+    }
+
+    /// Hand-picked range - fitting is a no-op unless quantiles are requested
+    pub fn fixed(min: f32, max: f32, levels: usize) -> Self {
+        Self {
+            range: RangeFit::Fixed { min, max },
+            ..Self::new(levels)
+        }
+    }
+
+    pub fn with_percentiles(mut self, low: f32, high: f32) -> Self {
+        self.range = RangeFit::Percentile { low, high };
+        self
+    }
+
+    pub fn with_quantiles(mut self) -> Self {
+        self.binning = Binning::Quantile;
+        self
+    }
+
+    pub fn with_log1p(mut self) -> Self {
+        self.transform = Transform::Log1p;
+        self
+    }
+
+    /// Resolve the range (and level boundaries) from the given values.
+    /// NaNs are ignored.
+    pub fn fit(&self, values: &[f32]) -> ScalarSpec {
+        let mut sorted: Vec<f32> = values
+            .iter()
+            .map(|&v| self.transform.apply(v))
+            .filter(|v| !v.is_nan())
+            .collect();
+        sorted.sort_by(f32::total_cmp);
+
+        let (min, max) = match self.range {
+            RangeFit::Fixed { min, max } => (self.transform.apply(min), self.transform.apply(max)),
+            RangeFit::MinMax => (percentile(&sorted, 0.0), percentile(&sorted, 100.0)),
+            RangeFit::Percentile { low, high } => {
+                (percentile(&sorted, low), percentile(&sorted, high))
+            }
+        };
+
+        let mut levels = self.levels;
+        let mut boundaries = Vec::new();
+        // without values in a fixed range to take quantiles of, equal-width levels
+        let clipped: Vec<f32> = match self.binning {
+            Binning::Quantile => sorted
+                .iter()
+                .copied()
+                .filter(|&v| v >= min && v <= max)
+                .collect(),
+            Binning::Uniform => Vec::new(),
+        };
+        if !clipped.is_empty() {
+            boundaries = (1..self.levels)
+                .map(|k| percentile(&clipped, 100.0 * k as f32 / self.levels as f32))
+                .collect();
+            // heavily repeated values (e.g. mostly zeros) give duplicate cut points,
+            // and a cut point at the smallest value would leave the first level empty
+            boundaries.dedup();
+            boundaries.retain(|&b| b > clipped[0]);
+            levels = boundaries.len() + 1;
+        }
+
+        ScalarSpec {
+            min,
+            max,
+            levels,
+            transform: self.transform,
+            boundaries,
+        }
+    }
+}
+
+// `p` in 0-100, nearest rank on sorted values
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    assert!(!sorted.is_empty(), "can not fit a range to zero values");
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f32).round() as usize;
+    sorted[rank]
+}
+
+/// Resolved ScalarEncoder parameters. `min`, `max` and `boundaries` are in transformed units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalarSpec {
+    pub min: f32,
+    pub max: f32,
+    pub levels: usize,
+    pub transform: Transform,
+    pub boundaries: Vec<f32>,
+}
+
+impl fmt::Display for ScalarSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:.5} max {:.5} levels {:3} transform {}",
+            self.min, self.max, self.levels, self.transform
+        )?;
+        if !self.boundaries.is_empty() {
+            write!(f, " quantiles {:?}", self.boundaries)?;
+        }
+        Ok(())
+    }
+}
+
+// ── InterpolatingEncoder ────────────────────────────────────────────────────────────
+//
+// Same level construction as ScalarEncoder, but without the `basis` table:
+// the vector for a value is computed on the fly by copying a value-proportional
+// number of components (in a fixed random order) from v_max into v_min.
+// Resolution is a single component, and memory does not depend on it.
+
+pub struct InterpolatingEncoder<H: HyperVector> {
+    pub min: f32,
+    pub max: f32,
+    v_min: H,
+    v_max: H,
+    indices: Vec<usize>, // order in which components are copied from v_max
+}
+
+impl<H: HyperVector> InterpolatingEncoder<H> {
+    pub fn new<R: Rng>(min: f32, max: f32, rng: &mut R) -> Self {
         let v_min = H::random(rng);
         let v_max = H::random(rng);
 
@@ -28,22 +396,30 @@ impl<H: HyperVector> ScalarEncoder<H> {
         let mut indices: Vec<usize> = (0..H::DIM).collect();
         indices.shuffle(rng);
 
-        let basis = (0..num_levels)
-            .map(|i| {
-                let frac = i as f32 / (num_levels - 1) as f32;
-                let num_to_swap = (frac * H::DIM as f32) as usize;
-                let current_indices = &indices[0..num_to_swap];
-                v_min.blend(&v_max, current_indices)
-            })
-            .collect();
+        Self {
+            min,
+            max,
+            v_min,
+            v_max,
+            indices,
+        }
+    }
 
-        Self { min, max, basis }
+    /// Vector at position `frac` (0..=1) between v_min and v_max.
+    pub fn at_fraction(&self, frac: f32) -> H {
+        let num_to_swap = (frac.clamp(0.0, 1.0) * H::DIM as f32) as usize;
+        self.v_min.blend(&self.v_max, &self.indices[0..num_to_swap])
     }
 
-    pub fn encode(&self, value: f32) -> &H {
-        let normalized = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
-        let index = (normalized * (self.basis.len() - 1) as f32) as usize;
-        &self.basis[index]
+    pub fn encode(&self, value: f32) -> H {
+        let normalized = (value - self.min) / (self.max - self.min);
+        self.at_fraction(normalized)
+    }
+}
+
+impl<H: HyperVector> Encoder<f32, H> for InterpolatingEncoder<H> {
+    fn encode(&self, value: &f32) -> H {
+        InterpolatingEncoder::encode(self, *value)
     }
 }
 
@@ -73,42 +449,494 @@ impl<T: HyperVector, const N: usize> BundleEncoder<T, N> {
     }
 }
 
+impl<T: HyperVector, const N: usize> Encoder<[f32; N], T> for BundleEncoder<T, N> {
+    fn encode(&self, features: &[f32; N]) -> T {
+        BundleEncoder::encode(self, features)
+    }
+}
+
+// ── Column schema ────────────────────────────────────────────────────────────────────
+//
+// Declares the columns of a table (e.g. a UCI style CSV file): how each raw token is
+// parsed into an f32 value, and how that value is encoded by the TabularEncoder.
+// Categorical, boolean and ordinal values are represented by their index.
+// Missing values (empty, "?", "NA", "NaN", ...) parse to None and are handled
+// according to the column's MissingPolicy.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnKind {
+    /// Continuous value - level encoded, range fitted to training data
+    Numeric(ScalarFit),
+    /// Unordered categories - one random vector per category
+    Categorical(Vec<String>),
+    /// true/false, yes/no, 1/0
+    Boolean,
+    /// Ordered categories - correlated vectors, so neighbouring levels are similar
+    Ordinal(Vec<String>),
+    /// Open vocabulary - values hashed to one of HASH_BUCKETS random vectors, so values
+    /// not seen in training are fine
+    Hashed,
+    /// Parsed, but not encoded
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingPolicy {
+    /// Leave the field out of the bundle
+    #[default]
+    Skip,
+    /// A dedicated random "missing" vector for the field
+    Vector,
+    /// Encode the training median (numeric, ordinal) or mode (categorical, boolean) -
+    /// a missing vector, as Vector, when the column has no training values
+    Impute,
+}
+
+impl fmt::Display for MissingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissingPolicy::Skip => write!(f, "skip"),
+            MissingPolicy::Vector => write!(f, "vector"),
+            MissingPolicy::Impute => write!(f, "impute"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnKind,
+    pub weight: f64, // weight of the column in the bundle
+    pub missing: MissingPolicy,
+}
+
+impl Column {
+    fn with_kind(name: &str, kind: ColumnKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            weight: 1.0,
+            missing: MissingPolicy::Skip,
+        }
+    }
+
+    pub fn numeric(name: &str, fit: ScalarFit) -> Self {
+        Self::with_kind(name, ColumnKind::Numeric(fit))
+    }
+
+    pub fn categorical(name: &str, categories: &[&str]) -> Self {
+        let categories = categories.iter().map(|c| c.to_string()).collect();
+        Self::with_kind(name, ColumnKind::Categorical(categories))
+    }
+
+    pub fn boolean(name: &str) -> Self {
+        Self::with_kind(name, ColumnKind::Boolean)
+    }
+
+    /// `levels` in increasing order
+    pub fn ordinal(name: &str, levels: &[&str]) -> Self {
+        let levels = levels.iter().map(|c| c.to_string()).collect();
+        Self::with_kind(name, ColumnKind::Ordinal(levels))
+    }
+
+    pub fn hashed(name: &str) -> Self {
+        Self::with_kind(name, ColumnKind::Hashed)
+    }
+
+    pub fn ignore(name: &str) -> Self {
+        Self::with_kind(name, ColumnKind::Ignore)
+    }
+
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_missing(mut self, missing: MissingPolicy) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Categorical and ordinal columns need at least one name
+    pub fn validate(&self) -> io::Result<()> {
+        let what = match &self.kind {
+            ColumnKind::Categorical(names) if names.is_empty() => "categories",
+            ColumnKind::Ordinal(names) if names.is_empty() => "levels",
+            _ => return Ok(()),
+        };
+        Err(invalid(format!("column {}: no {what}", self.name)))
+    }
+
+    // Number of values of a column of indices - None for numbers and hash buckets
+    fn cardinality(&self) -> Option<usize> {
+        match &self.kind {
+            ColumnKind::Categorical(names) | ColumnKind::Ordinal(names) => Some(names.len()),
+            ColumnKind::Boolean => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Parses a raw token - numbers as is, categories as their index, hashed values as
+    /// their bucket, None if missing.
+    /// A category named e.g. "?" takes precedence over the missing markers.
+    pub fn parse(&self, token: &str) -> io::Result<Option<f32>> {
+        if let ColumnKind::Categorical(names) | ColumnKind::Ordinal(names) = &self.kind
+            && let Some(i) = names.iter().position(|n| n == token)
+        {
+            return Ok(Some(i as f32));
+        }
+        if is_missing(token) {
+            return Ok(None);
+        }
+        let value = match &self.kind {
+            ColumnKind::Numeric(_) => f32::from_str(token)
+                .map_err(|e| invalid(format!("column {}: {e} ({token:?})", self.name)))?,
+            ColumnKind::Categorical(_) | ColumnKind::Ordinal(_) => {
+                return Err(invalid(format!(
+                    "column {}: unknown value {token:?}",
+                    self.name
+                )));
+            }
+            ColumnKind::Boolean => match token.to_ascii_lowercase().as_str() {
+                "1" | "true" | "t" | "yes" | "y" => 1.0,
+                "0" | "false" | "f" | "no" | "n" => 0.0,
+                _ => {
+                    return Err(invalid(format!(
+                        "column {}: not a boolean {token:?}",
+                        self.name
+                    )));
+                }
+            },
+            ColumnKind::Hashed => (HashEncoder::<str>::new(0).hash(token) % HASH_BUCKETS) as f32,
+            ColumnKind::Ignore => return Ok(None),
+        };
+        Ok(Some(value))
+    }
+}
+
+/// Buckets of a hashed column - bucket indices are exact in an f32
+pub const HASH_BUCKETS: u64 = 1 << 24;
+
+fn is_missing(token: &str) -> bool {
+    matches!(
+        token.to_ascii_lowercase().as_str(),
+        "" | "?" | "na" | "n/a" | "nan" | "null"
+    )
+}
+
+impl fmt::Display for ColumnKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColumnKind::Numeric(_) => write!(f, "numeric"),
+            ColumnKind::Categorical(names) => write!(f, "categorical ({})", names.len()),
+            ColumnKind::Boolean => write!(f, "boolean"),
+            ColumnKind::Ordinal(names) => write!(f, "ordinal ({})", names.len()),
+            ColumnKind::Hashed => write!(f, "hashed"),
+            ColumnKind::Ignore => write!(f, "ignored"),
+        }
+    }
+}
+
+/// Parses one row of raw tokens, one per column.
+pub fn parse_row<S: AsRef<str>>(columns: &[Column], tokens: &[S]) -> io::Result<Vec<Option<f32>>> {
+    if tokens.len() != columns.len() {
+        return Err(invalid(format!(
+            "expected {} tokens, got {}",
+            columns.len(),
+            tokens.len()
+        )));
+    }
+    for column in columns {
+        column.validate()?;
+    }
+    columns
+        .iter()
+        .zip(tokens)
+        .map(|(column, token)| column.parse(token.as_ref().trim()))
+        .collect()
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
 // ── TabularEncoder ───────────────────────────────────────────────────────────────────
 //
+// Each column value is encoded by its field encoder, bound to the column key,
+// and the weighted bundle over the columns is the row vector.
+
+pub enum FieldEncoder<H: HyperVector> {
+    Scalar(ScalarEncoder<H>),
+    Categorical(CategoricalEncoder<H>),
+    Hashed(HashEncoder<u32>),
+    Ignore,
+}
+
+impl<H: HyperVector> FieldEncoder<H> {
+    #[inline]
+    pub fn encode(&self, value: f32) -> Option<Cow<'_, H>> {
+        match self {
+            FieldEncoder::Scalar(encoder) => Some(Cow::Borrowed(encoder.encode(value))),
+            FieldEncoder::Categorical(encoder) => {
+                Some(Cow::Borrowed(encoder.encode(value as usize)))
+            }
+            FieldEncoder::Hashed(encoder) => Some(Cow::Owned(encoder.encode(&(value as u32)))),
+            FieldEncoder::Ignore => None,
+        }
+    }
+}
+
+/// A MissingPolicy resolved against the training data
+pub enum Missing<H: HyperVector> {
+    Skip,
+    Vector(H),
+    Impute(f32),
+}
 
 pub struct TabularEncoder<H: HyperVector> {
-    // Vectors indexed by the column position in the CSV
-    pub field_encoders: Vec<ScalarEncoder<H>>,
+    // Indexed by the column position in the CSV
+    pub columns: Vec<Column>,
+    pub field_encoders: Vec<FieldEncoder<H>>,
     pub field_keys: Vec<H>,
+    pub field_missing: Vec<Missing<H>>,
 }
 
 impl<H: HyperVector> TabularEncoder<H> {
     /// Initialize from a slice of (min, max, resolution)
     pub fn new(schema: &[(f32, f32, usize)], rng: &mut impl Rng) -> Self {
-        let mut field_encoders = Vec::with_capacity(schema.len());
-        let mut field_keys = Vec::with_capacity(schema.len());
+        let specs: Vec<ScalarSpec> = schema
+            .iter()
+            .map(|&(min, max, levels)| ScalarFit::fixed(min, max, levels).fit(&[]))
+            .collect();
+        Self::from_specs(&specs, rng)
+    }
 
-        for &(min, max, levels) in schema {
-            field_encoders.push(ScalarEncoder::new(min, max, levels, rng));
+    /// Initialize from resolved per-column parameters, e.g. the `specs` of a fitted encoder
+    pub fn from_specs(specs: &[ScalarSpec], rng: &mut impl Rng) -> Self {
+        let mut columns = Vec::with_capacity(specs.len());
+        let mut field_encoders = Vec::with_capacity(specs.len());
+        let mut field_keys = Vec::with_capacity(specs.len());
+
+        for (i, spec) in specs.iter().enumerate() {
+            let (min, max) = (
+                spec.transform.invert(spec.min),
+                spec.transform.invert(spec.max),
+            );
+            let mut fit = ScalarFit::fixed(min, max, spec.levels);
+            fit.transform = spec.transform;
+            if !spec.boundaries.is_empty() {
+                fit.binning = Binning::Quantile;
+            }
+            columns.push(Column::numeric(&i.to_string(), fit));
+            field_encoders.push(FieldEncoder::Scalar(ScalarEncoder::from_spec(spec, rng)));
             field_keys.push(H::random(rng));
         }
 
         Self {
+            field_missing: columns.iter().map(|_| Missing::Skip).collect(),
+            columns,
             field_encoders,
             field_keys,
         }
     }
 
-    /// Encodes a row of features. Assumes row.len() == schema.len()
-    pub fn encode(&self, row: &[f32]) -> H {
+    /// Fit per-column parameters to training rows - one ScalarFit per column.
+    /// The fitted encoder is then used as is on test data. NaN values are missing.
+    /// Fails like `from_columns`, e.g. on a row of the wrong length.
+    pub fn fit<S: AsRef<[f32]>>(
+        rows: &[S],
+        fits: &[ScalarFit],
+        rng: &mut impl Rng,
+    ) -> io::Result<Self> {
+        let columns: Vec<Column> = fits
+            .iter()
+            .enumerate()
+            .map(|(i, &fit)| Column::numeric(&i.to_string(), fit))
+            .collect();
+        let rows: Vec<Vec<Option<f32>>> = rows.iter().map(|row| present(row.as_ref())).collect();
+        Self::from_columns(&columns, &rows, rng)
+    }
+
+    /// Build an encoder for a mixed-type table. Numeric columns (and imputed values)
+    /// are fitted to the (parsed) training rows, see `parse_row`. Fails on a
+    /// categorical or ordinal column without names, a row of the wrong length, or a
+    /// category index out of range.
+    pub fn from_columns<S: AsRef<[Option<f32>]>>(
+        columns: &[Column],
+        rows: &[S],
+        rng: &mut impl Rng,
+    ) -> io::Result<Self> {
+        for column in columns {
+            column.validate()?;
+        }
+        for row in rows {
+            let row = row.as_ref();
+            if row.len() != columns.len() {
+                return Err(invalid(format!(
+                    "expected {} values, got {}",
+                    columns.len(),
+                    row.len()
+                )));
+            }
+            for (column, &value) in columns.iter().zip(row) {
+                if let (Some(n), Some(v)) = (column.cardinality(), value)
+                    && !(v >= 0.0 && v < n as f32 && v.fract() == 0.0)
+                {
+                    return Err(invalid(format!(
+                        "column {}: index {v} out of range for {}",
+                        column.name, column.kind
+                    )));
+                }
+            }
+        }
+
+        let mut field_encoders = Vec::with_capacity(columns.len());
+        let mut field_keys = Vec::with_capacity(columns.len());
+        let mut field_missing = Vec::with_capacity(columns.len());
+
+        for (col, column) in columns.iter().enumerate() {
+            let values: Vec<f32> = rows.iter().filter_map(|row| row.as_ref()[col]).collect();
+            if let ColumnKind::Numeric(fit) = &column.kind
+                && values.is_empty()
+                && !matches!(fit.range, RangeFit::Fixed { .. })
+            {
+                return Err(invalid(format!(
+                    "column {}: no values to fit the range to",
+                    column.name
+                )));
+            }
+            let encoder = match &column.kind {
+                ColumnKind::Numeric(fit) => {
+                    FieldEncoder::Scalar(ScalarEncoder::fit(&values, fit, rng))
+                }
+                ColumnKind::Categorical(names) => {
+                    FieldEncoder::Categorical(CategoricalEncoder::new(names.len(), rng))
+                }
+                ColumnKind::Boolean => FieldEncoder::Categorical(CategoricalEncoder::new(2, rng)),
+                ColumnKind::Ordinal(names) => {
+                    // one level per name, cut points half way between the indices
+                    let n = names.len();
+                    let spec = ScalarSpec {
+                        min: 0.0,
+                        max: (n - 1) as f32,
+                        levels: n,
+                        transform: Transform::Identity,
+                        boundaries: (1..n).map(|i| i as f32 - 0.5).collect(),
+                    };
+                    FieldEncoder::Scalar(ScalarEncoder::from_spec(&spec, rng))
+                }
+                ColumnKind::Hashed => FieldEncoder::Hashed(HashEncoder::new(rng.next_u64())),
+                ColumnKind::Ignore => FieldEncoder::Ignore,
+            };
+            field_encoders.push(encoder);
+            field_keys.push(match column.kind {
+                ColumnKind::Ignore => H::ident(),
+                _ => H::random(rng),
+            });
+            field_missing.push(match (&column.kind, column.missing) {
+                (ColumnKind::Ignore, _) | (_, MissingPolicy::Skip) => Missing::Skip,
+                (_, MissingPolicy::Vector) => Missing::Vector(H::random(rng)),
+                // nothing to impute from
+                (_, MissingPolicy::Impute) if values.is_empty() => Missing::Vector(H::random(rng)),
+                (ColumnKind::Numeric(_) | ColumnKind::Ordinal(_), MissingPolicy::Impute) => {
+                    Missing::Impute(median(&values))
+                }
+                (_, MissingPolicy::Impute) => Missing::Impute(mode(&values)),
+            });
+        }
+
+        Ok(Self {
+            columns: columns.to_vec(),
+            field_encoders,
+            field_keys,
+            field_missing,
+        })
+    }
+
+    /// Encodes a row of features, None for missing values. Assumes row.len() == columns.len()
+    pub fn encode(&self, row: &[Option<f32>]) -> H {
         let mut acc = H::Accumulator::new();
-        for ((&value, encoder), key) in row.iter().zip(&self.field_encoders).zip(&self.field_keys) {
-            let val_v = encoder.encode(value);
-            let bound = val_v.bind(key);
-            acc.add(&bound, 1.0);
+        for (col, &value) in row.iter().enumerate() {
+            let encoder = &self.field_encoders[col];
+            let val_v = match (value, &self.field_missing[col]) {
+                (Some(x), _) => encoder.encode(x),
+                (None, Missing::Skip) => None,
+                (None, Missing::Vector(v)) => Some(Cow::Borrowed(v)),
+                (None, Missing::Impute(x)) => encoder.encode(*x),
+            };
+            if let Some(val_v) = val_v {
+                let bound = val_v.bind(&self.field_keys[col]);
+                acc.add(&bound, self.columns[col].weight);
+            }
         }
         acc.finalize()
     }
+
+    /// Encodes a row of plain values - NaN values are missing.
+    pub fn encode_values(&self, row: &[f32]) -> H {
+        self.encode(&present(row))
+    }
+
+    /// The resolved parameters of the numeric columns, in column order - report them
+    /// to make an experiment reproducible, and rebuild the encoder with `from_specs`
+    pub fn specs(&self) -> Vec<ScalarSpec> {
+        self.columns
+            .iter()
+            .zip(&self.field_encoders)
+            .filter_map(|(column, encoder)| match (&column.kind, encoder) {
+                (ColumnKind::Numeric(_), FieldEncoder::Scalar(e)) => Some(e.spec()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<H: HyperVector> Encoder<[Option<f32>], H> for TabularEncoder<H> {
+    fn encode(&self, row: &[Option<f32>]) -> H {
+        TabularEncoder::encode(self, row)
+    }
+}
+
+fn present(row: &[f32]) -> Vec<Option<f32>> {
+    row.iter().map(|&v| (!v.is_nan()).then_some(v)).collect()
+}
+
+fn median(values: &[f32]) -> f32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+// most frequent category index (or hash bucket)
+fn mode(values: &[f32]) -> f32 {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for &v in values {
+        *counts.entry(v as usize).or_default() += 1;
+    }
+    let best = counts
+        .into_iter()
+        .max_by_key(|&(i, count)| (count, std::cmp::Reverse(i)));
+    best.map_or(0, |(i, _)| i) as f32
+}
+
+/// The fitted schema, one line per column - print it to make an experiment reproducible.
+impl<H: HyperVector> fmt::Display for TabularEncoder<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (column, encoder) in self.columns.iter().zip(&self.field_encoders) {
+            write!(
+                f,
+                "{:16} {:18} weight {:.2} missing {:6}",
+                column.name, column.kind, column.weight, column.missing
+            )?;
+            if let FieldEncoder::Scalar(e) = encoder
+                && matches!(column.kind, ColumnKind::Numeric(_))
+            {
+                write!(f, " {}", e.spec())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 // ── CategoricalEncoder ────────────────────────────────────────────────────────
@@ -130,14 +958,202 @@ impl<H: HyperVector> CategoricalEncoder<H> {
     }
 }
 
+impl<H: HyperVector> Encoder<usize, H> for CategoricalEncoder<H> {
+    fn encode(&self, idx: &usize) -> H {
+        CategoricalEncoder::encode(self, *idx).clone()
+    }
+}
+
+// ── HashEncoder ───────────────────────────────────────────────────────────────
+//
+// Open-vocabulary categories (product ids, country names, ...): the vector for a value
+// is drawn from an rng seeded with the hash of (seed, value), so nothing is stored and
+// unseen values at inference time simply get their own random vector. The same seed
+// gives the same vectors in every run, process and machine: the hash is FNV-1a rather
+// than std's randomly keyed SipHash, over the little-endian seed and the value's
+// StableBytes - UTF-8 for strings, little endian in their own width for integers.
+// The Codebook below hashes names the same way. usize is left out, as its width
+// depends on the platform.
+
+/// FNV-1a, 64 bit - stable across runs, unlike std's RandomState
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Values with the same bytes on every platform, see HashEncoder
+pub trait StableBytes {
+    fn write_to(&self, hasher: &mut StableHasher);
+}
+
+impl StableBytes for str {
+    fn write_to(&self, hasher: &mut StableHasher) {
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl StableBytes for String {
+    fn write_to(&self, hasher: &mut StableHasher) {
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl StableBytes for [u8] {
+    fn write_to(&self, hasher: &mut StableHasher) {
+        hasher.write(self);
+    }
+}
+
+macro_rules! stable_bytes_le {
+    ($($t:ty),*) => {
+        $(impl StableBytes for $t {
+            fn write_to(&self, hasher: &mut StableHasher) {
+                hasher.write(&self.to_le_bytes());
+            }
+        })*
+    };
+}
+
+stable_bytes_le!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+pub struct HashEncoder<T: ?Sized> {
+    pub seed: u64,
+    _value: PhantomData<fn(&T)>,
+}
+
+impl<T: StableBytes + ?Sized> HashEncoder<T> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            _value: PhantomData,
+        }
+    }
+
+    /// The rng seed for `value`
+    pub fn hash(&self, value: &T) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write(&self.seed.to_le_bytes());
+        value.write_to(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn encode<H: HyperVector>(&self, value: &T) -> H {
+        H::random(&mut MersenneTwister64::new(self.hash(value)))
+    }
+}
+
+impl<T: StableBytes + ?Sized, H: HyperVector> Encoder<T, H> for HashEncoder<T> {
+    fn encode(&self, value: &T) -> H {
+        HashEncoder::encode(self, value)
+    }
+}
+
+// ── Codebook ──────────────────────────────────────────────────────────────────
+//
+// Named symbols (characters, words, ...) with vectors derived from (seed, name): the
+// FNV-1a hash of the little-endian seed and the UTF-8 name seeds the rng that draws
+// the vector - the vector of a HashEncoder<str> with the same seed. Unlike drawing symbols from a shared rng in the order they are first
+// seen, a symbol's vector does not depend on which other symbols exist, and it is
+// the same on every run and machine - a codebook can be regenerated from its seed
+// instead of being stored.
+
+pub struct Codebook<H: HyperVector> {
+    pub seed: u64,
+    symbols: HashMap<String, H>, // generated so far
+}
+
+impl<H: HyperVector> Codebook<H> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            symbols: HashMap::new(),
+        }
+    }
+
+    /// The vector for `name` in the codebook with `seed` - nothing is cached
+    pub fn generate(seed: u64, name: &str) -> H {
+        HashEncoder::<str>::new(seed).encode(name)
+    }
+
+    /// Codebook with the vectors of `names` generated up front
+    pub fn with_symbols<S: AsRef<str>>(seed: u64, names: &[S]) -> Self {
+        let mut codebook = Self::new(seed);
+        for name in names {
+            codebook.get(name.as_ref());
+        }
+        codebook
+    }
+
+    pub fn get(&mut self, name: &str) -> &H {
+        if !self.symbols.contains_key(name) {
+            let v = Self::generate(self.seed, name);
+            self.symbols.insert(name.to_string(), v);
+        }
+        &self.symbols[name]
+    }
+
+    pub fn get_char(&mut self, c: char) -> &H {
+        self.get(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// The symbol closest to `query` among those generated so far
+    pub fn nearest(&self, query: &H) -> Option<(&str, f32)> {
+        self.symbols
+            .iter()
+            .map(|(name, v)| (name.as_str(), query.distance(v)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Symbols generated so far, in no particular order
+    pub fn symbols(&self) -> impl Iterator<Item = (&str, &H)> {
+        self.symbols.iter().map(|(name, v)| (name.as_str(), v))
+    }
+}
+
 // -- FWHT ------------------------------------------------------------------
 
 pub trait FromSpectrum {
+    /// Number of spectrum coefficients consumed by `from_spectrum`
+    const SPECTRUM_LEN: usize;
+
     /// Constructs the hypervector from a raw real-valued spectrum.
     fn from_spectrum(slice: &[f32]) -> Self;
 }
 
 impl<const N: usize> FromSpectrum for Binary<N, true> {
+    const SPECTRUM_LEN: usize = Self::DIM;
+
     fn from_spectrum(coefficients: &[f32]) -> Self {
         let iter = coefficients
             .iter()
@@ -147,6 +1163,8 @@ impl<const N: usize> FromSpectrum for Binary<N, true> {
 }
 
 impl<const N: usize> FromSpectrum for Binary<N, false> {
+    const SPECTRUM_LEN: usize = Self::DIM;
+
     fn from_spectrum(coefficients: &[f32]) -> Self {
         let iter = coefficients
             .iter()
@@ -156,25 +1174,49 @@ impl<const N: usize> FromSpectrum for Binary<N, false> {
 }
 
 impl<const N: usize> FromSpectrum for RealHDV<N> {
+    const SPECTRUM_LEN: usize = N;
+
     fn from_spectrum(coefficients: &[f32]) -> Self {
         Self::from_slice(coefficients)
     }
 }
 
+// Complex and modular elements are phases: each pair of coefficients is a point
+// in the plane, and its angle is the phase of one element.
+
 impl<const N: usize> FromSpectrum for ComplexHDV<N> {
-    fn from_spectrum(_coefficients: &[f32]) -> Self {
-        unimplemented!()
+    const SPECTRUM_LEN: usize = 2 * N;
+
+    fn from_spectrum(coefficients: &[f32]) -> Self {
+        // unit phasors, scaled so that the vector has unit norm like ComplexHDV::random
+        let r = 1.0 / (N as f32).sqrt();
+        let iter = coefficients
+            .chunks_exact(2)
+            .map(|c| Complex::from_polar(r, c[1].atan2(c[0])));
+        Self::from_iter(iter)
     }
 }
 
 impl<const N: usize> FromSpectrum for Modular<N> {
-    fn from_spectrum(_coefficients: &[f32]) -> Self {
-        unimplemented!()
+    const SPECTRUM_LEN: usize = 2 * N;
+
+    fn from_spectrum(coefficients: &[f32]) -> Self {
+        let iter = coefficients
+            .chunks_exact(2)
+            .map(|c| quantize_phase(c[1], c[0]));
+        Self::from_iter(iter)
     }
 }
 
+// Subsampled randomized Hadamard transform: the features are zero padded to a power
+// of two block, multiplied by random signs and transformed. Blocks with independent
+// signs are stacked until T::SPECTRUM_LEN coefficients are available - or, if there
+// are more features than coefficients, a random subset of one block is kept.
+
 pub struct FwhtEncoder<T, const FEATURE_DIM: usize> {
-    random_base: Vec<f32>,
+    block: usize,
+    signs: Vec<Vec<f32>>, // one sign vector per block
+    rows: Vec<usize>,     // coefficients kept, T::SPECTRUM_LEN of them
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -183,33 +1225,177 @@ where
     T: FromSpectrum + HyperVector,
 {
     pub fn new(rng: &mut impl Rng) -> Self {
-        assert!(T::DIM.is_power_of_two());
-        let random_base = (0..T::DIM)
-            .map(|_| if rng.random_bool(0.5) { 1.0 } else { -1.0 })
+        let block = FEATURE_DIM.next_power_of_two();
+        let len = T::SPECTRUM_LEN;
+        let signs = (0..len.div_ceil(block))
+            .map(|_| {
+                (0..FEATURE_DIM)
+                    .map(|_| if rng.random_bool(0.5) { 1.0 } else { -1.0 })
+                    .collect()
+            })
             .collect();
+        let rows = if len < block {
+            let mut rows = rand::seq::index::sample(rng, block, len).into_vec();
+            rows.sort_unstable();
+            rows
+        } else {
+            (0..len).collect()
+        };
 
         Self {
-            random_base,
+            block,
+            signs,
+            rows,
             _marker: std::marker::PhantomData,
         }
     }
 
     pub fn encode(&self, features: &[f32; FEATURE_DIM]) -> T {
-        // Tile the features across the entire D-dimensional space.
-        // self.random_base already contains D random signs.
-
-        //Can't use array in stable rust yet (1.95.0) ...
-        //let mut buffer: [f32; T::DIM] =
-        //    std::array::from_fn(|i| features[i % FEATURE_DIM] * self.random_base[i]);
-        let mut buffer: Vec<f32> = (0..T::DIM)
-            .map(|i| features[i % FEATURE_DIM] * self.random_base[i])
+        let mut spectrum = vec![0.0f32; self.signs.len() * self.block];
+        for (buffer, signs) in spectrum.chunks_exact_mut(self.block).zip(&self.signs) {
+            for ((b, &x), &s) in buffer.iter_mut().zip(features).zip(signs) {
+                *b = x * s;
+            }
+            // Fast Walsh-Hadamard Transform
+            fwht(buffer);
+        }
+
+        // Map cleanly to the target type via our isolated trait
+        let coefficients: Vec<f32> = self.rows.iter().map(|&i| spectrum[i]).collect();
+        T::from_spectrum(&coefficients)
+    }
+}
+
+impl<T, const FEATURE_DIM: usize> Encoder<[f32; FEATURE_DIM], T> for FwhtEncoder<T, FEATURE_DIM>
+where
+    T: FromSpectrum + HyperVector,
+{
+    fn encode(&self, features: &[f32; FEATURE_DIM]) -> T {
+        FwhtEncoder::encode(self, features)
+    }
+}
+
+// -- Random Fourier features ----------------------------------------------
+//
+// Nonlinear encoder (Rahimi & Recht, "Random Features for Large-Scale Kernel
+// Machines", 2007): dimension j holds the phase w_j·x + b_j, with w_j drawn from
+// the Fourier transform of the kernel and b_j uniform in [0, 2π). Similarity of
+// the encoded vectors then approximates the kernel k(x - y).
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kernel {
+    /// exp(-|x - y|² / 2σ²) - w ~ Normal(0, 1/σ²)
+    Rbf { bandwidth: f32 },
+    /// exp(-|x - y|₁ / σ) - w ~ Cauchy(0, 1/σ)
+    Laplacian { bandwidth: f32 },
+}
+
+impl fmt::Display for Kernel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kernel::Rbf { bandwidth } => write!(f, "rbf (bandwidth {bandwidth})"),
+            Kernel::Laplacian { bandwidth } => write!(f, "laplacian (bandwidth {bandwidth})"),
+        }
+    }
+}
+
+pub trait FromPhases {
+    /// Constructs the hypervector from one phase angle (radians) per dimension.
+    fn from_phases(phases: &[f32]) -> Self;
+}
+
+// binary: sign of cos(phase)
+impl<const N: usize> FromPhases for Binary<N, true> {
+    fn from_phases(phases: &[f32]) -> Self {
+        Self::from_iter(phases.iter().map(|p| if p.cos() < 0.0 { -1 } else { 1 }))
+    }
+}
+
+impl<const N: usize> FromPhases for Binary<N, false> {
+    fn from_phases(phases: &[f32]) -> Self {
+        Self::from_iter(phases.iter().map(|p| if p.cos() < 0.0 { 0 } else { 1 }))
+    }
+}
+
+// real: sqrt(2/N) cos(phase) - the classic random Fourier feature, unit norm in expectation
+impl<const N: usize> FromPhases for RealHDV<N> {
+    fn from_phases(phases: &[f32]) -> Self {
+        let scale = (2.0 / N as f32).sqrt();
+        let values: Vec<f32> = phases.iter().map(|p| scale * p.cos()).collect();
+        Self::from_slice(&values)
+    }
+}
+
+impl<const N: usize> FromPhases for ComplexHDV<N> {
+    fn from_phases(phases: &[f32]) -> Self {
+        let r = 1.0 / (N as f32).sqrt();
+        Self::from_iter(phases.iter().map(|&p| Complex::from_polar(r, p)))
+    }
+}
+
+impl<const N: usize> FromPhases for Modular<N> {
+    fn from_phases(phases: &[f32]) -> Self {
+        Self::from_iter(phases.iter().map(|p| quantize_phase(p.sin(), p.cos())))
+    }
+}
+
+pub struct RffEncoder<T, const FEATURE_DIM: usize> {
+    pub kernel: Kernel,
+    weights: Vec<f32>, // T::DIM rows of FEATURE_DIM
+    offsets: Vec<f32>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T, const FEATURE_DIM: usize> RffEncoder<T, FEATURE_DIM>
+where
+    T: FromPhases + HyperVector,
+{
+    pub fn new(kernel: Kernel, rng: &mut impl Rng) -> Self {
+        let (Kernel::Rbf { bandwidth } | Kernel::Laplacian { bandwidth }) = kernel;
+        assert!(
+            bandwidth > 0.0 && bandwidth.is_finite(),
+            "bandwidth must be > 0"
+        );
+        let n = T::DIM * FEATURE_DIM;
+        let weights = match kernel {
+            Kernel::Rbf { bandwidth } => {
+                let normal = Normal::new(0.0, 1.0 / bandwidth).unwrap();
+                (0..n).map(|_| normal.sample(rng)).collect()
+            }
+            Kernel::Laplacian { bandwidth } => {
+                let cauchy = Cauchy::new(0.0, 1.0 / bandwidth).unwrap();
+                (0..n).map(|_| cauchy.sample(rng)).collect()
+            }
+        };
+        let offsets = (0..T::DIM)
+            .map(|_| rng.random_range(0.0..std::f32::consts::TAU))
             .collect();
 
-        // Fast Walsh-Hadamard Transform
-        fwht(&mut buffer);
+        Self {
+            kernel,
+            weights,
+            offsets,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn encode(&self, features: &[f32; FEATURE_DIM]) -> T {
+        let phases: Vec<f32> = self
+            .weights
+            .chunks_exact(FEATURE_DIM)
+            .zip(&self.offsets)
+            .map(|(w, &b)| w.iter().zip(features).map(|(w, x)| w * x).sum::<f32>() + b)
+            .collect();
+        T::from_phases(&phases)
+    }
+}
 
-        // Map cleanly to the target type via our isolated trait
-        T::from_spectrum(&buffer)
+impl<T, const FEATURE_DIM: usize> Encoder<[f32; FEATURE_DIM], T> for RffEncoder<T, FEATURE_DIM>
+where
+    T: FromPhases + HyperVector,
+{
+    fn encode(&self, features: &[f32; FEATURE_DIM]) -> T {
+        RffEncoder::encode(self, features)
     }
 }
 
@@ -221,6 +1407,53 @@ mod encoding_tests {
     use crate::types::binary::Binary;
     use mersenne_twister_rs::MersenneTwister64;
 
+    #[test]
+    fn test_hash_encoder() {
+        let encoder = HashEncoder::<str>::new(7);
+        let a: Binary<64> = encoder.encode("SKU-000123");
+        // no codebook - a second encoder with the same seed gives the same vectors
+        assert_eq!(a, HashEncoder::<str>::new(7).encode("SKU-000123"));
+        assert_eq!(a, encoder.encode_batch(&["SKU-000123"])[0]);
+        let b: Binary<64> = encoder.encode("SKU-000124");
+        assert!((a.distance(&b) - 0.5).abs() < 0.05);
+        let c: Binary<64> = HashEncoder::<str>::new(8).encode("SKU-000123");
+        assert!((a.distance(&c) - 0.5).abs() < 0.05);
+
+        // FNV-1a test vector
+        let mut hasher = StableHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        // the little-endian seed and value bytes, whatever the platform
+        let mut hasher = StableHasher::new();
+        hasher.write(&[7, 0, 0, 0, 0, 0, 0, 0, 0x34, 0x12, 0, 0]);
+        assert_eq!(HashEncoder::<u32>::new(7).hash(&0x1234), hasher.finish());
+        assert_eq!(
+            HashEncoder::<[u8]>::new(7).hash(&[0x34, 0x12, 0, 0]),
+            hasher.finish()
+        );
+        assert_eq!(
+            a,
+            Codebook::<Binary<64>>::generate(7, "SKU-000123"),
+            "one convention for hashed values and codebook symbols"
+        );
+    }
+
+    #[test]
+    fn test_codebook() {
+        let mut a = Codebook::<Binary<16>>::new(42);
+        let mut b = Codebook::<Binary<16>>::with_symbols(42, &["x", "y", "é"]);
+        // vectors do not depend on the order or the other symbols
+        let e = a.get_char('é').clone();
+        assert_eq!(&e, b.get("é"));
+        assert_eq!(e, Codebook::generate(42, "é"));
+        assert_eq!(a.len(), 1);
+        assert_eq!(b.len(), 3);
+        assert!((e.distance(b.get("x")) - 0.5).abs() < 0.1);
+        assert!((e.distance(&Codebook::generate(43, "é")) - 0.5).abs() < 0.1);
+        assert_eq!(b.nearest(&e), Some(("é", 0.0)));
+    }
+
     #[test]
     fn test_scalar_correlation_gradient() {
         let mut mt = MersenneTwister64::new(42);
@@ -245,6 +1478,347 @@ mod encoding_tests {
         assert!(dist_0_9 > 0.4);
     }
 
+    #[test]
+    fn test_interpolating_resolution() {
+        let mut mt = MersenneTwister64::new(42);
+        let encoder = InterpolatingEncoder::<Binary<16>>::new(0.0, 1.0, &mut mt);
+        let v0 = encoder.encode(0.0);
+
+        // Distance from the min vector grows with the value, without quantization
+        let d1 = v0.distance(&encoder.encode(0.1));
+        let d2 = v0.distance(&encoder.encode(0.101));
+        let d3 = v0.distance(&encoder.encode(0.5));
+        assert!(d1 <= d2 && d2 < d3);
+
+        // Values between levels of a ScalarEncoder get their own vector
+        let v_a = encoder.encode(0.3);
+        let v_b = encoder.encode(0.31);
+        assert!(v_a != v_b);
+        assert!(v_a.distance(&v_b) < 0.02);
+
+        // Out of range values clamp to the end points
+        assert_eq!(encoder.encode(-1.0), v0);
+        assert_eq!(encoder.encode(2.0), encoder.encode(1.0));
+    }
+
+    #[test]
+    fn test_fit_range_and_quantiles() {
+        let values: Vec<f32> = (0..=100).map(|i| i as f32).chain([10_000.0]).collect();
+
+        let spec = ScalarFit::new(8).fit(&values);
+        assert_eq!((spec.min, spec.max, spec.levels), (0.0, 10_000.0, 8));
+
+        // percentile clipping ignores the outlier
+        let spec = ScalarFit::new(8).with_percentiles(1.0, 99.0).fit(&values);
+        assert!(spec.min >= 1.0 && spec.max <= 100.0);
+
+        // equal-frequency levels: 4 levels => 3 cut points at the quartiles
+        let spec = ScalarFit::new(4).with_quantiles().fit(&values);
+        assert_eq!(spec.boundaries.len(), 3);
+        assert!((spec.boundaries[1] - 51.0).abs() <= 1.0);
+
+        let spec = ScalarFit::new(4).with_log1p().fit(&values);
+        assert!((spec.max - 10_000f32.ln_1p()).abs() < 1e-4);
+
+        // quantiles of no values in the range fall back to equal-width levels
+        let fit = ScalarFit::fixed(-2.0, -1.0, 4).with_quantiles();
+        for values in [&values[..], &[]] {
+            let spec = fit.fit(values);
+            assert_eq!((spec.levels, spec.boundaries.len()), (4, 0));
+        }
+    }
+
+    #[test]
+    fn test_fitted_encoder_levels() {
+        let mut mt = MersenneTwister64::new(42);
+        // mostly zeros - duplicate quantile boundaries are merged
+        let values: Vec<f32> = (0..100)
+            .map(|i| if i < 80 { 0.0 } else { i as f32 })
+            .collect();
+        let fit = ScalarFit::new(10).with_quantiles();
+        let encoder = ScalarEncoder::<Binary<16>>::fit(&values, &fit, &mut mt);
+        let spec = encoder.spec();
+        assert!(spec.levels < 10);
+        assert_eq!(encoder.basis.len(), spec.levels);
+        assert_eq!(encoder.level(0.0), 0);
+        assert_eq!(encoder.level(1000.0), spec.levels - 1);
+
+        // re-creating from the reported spec gives the same levels
+        let copy = ScalarEncoder::<Binary<16>>::from_spec(&spec, &mut mt);
+        for &v in &values {
+            assert_eq!(encoder.level(v), copy.level(v));
+        }
+    }
+
+    #[test]
+    fn test_tabular_specs() {
+        let rows: Vec<[f32; 3]> = (0..200)
+            .map(|i| [i as f32, (i * i) as f32, (i % 7) as f32])
+            .collect();
+        let fits = [
+            ScalarFit::new(16),
+            ScalarFit::new(8).with_log1p().with_quantiles(),
+            ScalarFit::fixed(0.0, 10.0, 4),
+        ];
+        let encoder =
+            TabularEncoder::<Binary<16>>::fit(&rows, &fits, &mut MersenneTwister64::new(3))
+                .unwrap();
+        let specs = encoder.specs();
+        assert_eq!(specs.len(), 3);
+        assert_eq!(specs[1].transform, Transform::Log1p);
+        assert!(!specs[1].boundaries.is_empty());
+
+        // the reported schema rebuilds the same encoder, given the same rng
+        let copy = TabularEncoder::<Binary<16>>::from_specs(&specs, &mut MersenneTwister64::new(3));
+        assert_eq!(copy.specs(), specs);
+        let ColumnKind::Numeric(fit) = copy.columns[1].kind else {
+            panic!("numeric column")
+        };
+        assert_eq!(
+            (fit.transform, fit.binning),
+            (Transform::Log1p, Binning::Quantile)
+        );
+        for row in &rows {
+            assert_eq!(copy.encode_values(row), encoder.encode_values(row));
+        }
+    }
+
+    #[test]
+    fn test_mixed_columns() {
+        let mut mt = MersenneTwister64::new(42);
+        let columns = [
+            Column::numeric("x", ScalarFit::new(8)),
+            Column::categorical("colour", &["red", "green", "blue"]),
+            Column::boolean("flag"),
+            Column::ordinal("size", &["S", "M", "L", "XL"]),
+            Column::ignore("id"),
+        ];
+        let row = parse_row(&columns, &["2.5", "blue", "Yes", "L", "abc"]).unwrap();
+        assert_eq!(row, vec![Some(2.5), Some(2.0), Some(1.0), Some(2.0), None]);
+        assert!(parse_row(&columns, &["2.5", "pink", "yes", "L", "abc"]).is_err());
+        assert!(parse_row(&columns, &["2.5", "red"]).is_err());
+        let no_levels = [Column::ordinal("size", &[])];
+        assert!(parse_row(&no_levels, &["?"]).is_err());
+        assert!(
+            TabularEncoder::<RealHDV<64>>::from_columns(&no_levels, &[[None]], &mut mt).is_err()
+        );
+        // category indices out of range
+        for value in [3.0, -1.0, 0.5] {
+            let rows = [[Some(value)]];
+            assert!(
+                TabularEncoder::<RealHDV<64>>::from_columns(&columns[1..2], &rows, &mut mt)
+                    .is_err()
+            );
+        }
+
+        let rows = vec![
+            vec![Some(0.0), Some(0.0), Some(0.0), Some(0.0), None],
+            vec![Some(10.0), Some(1.0), Some(1.0), Some(3.0), None],
+        ];
+        let encoder =
+            TabularEncoder::<RealHDV<64>>::from_columns(&columns, &rows, &mut mt).unwrap();
+        let FieldEncoder::Scalar(size) = &encoder.field_encoders[3] else {
+            panic!("ordinal column should be level encoded");
+        };
+        for i in 0..4 {
+            assert_eq!(size.level(i as f32), i);
+        }
+        assert!(matches!(encoder.field_encoders[4], FieldEncoder::Ignore));
+
+        // the ignored column does not change the encoding (RealHDV bundling is deterministic)
+        let mut other = rows[1].clone();
+        other[4] = Some(7.0);
+        assert_eq!(encoder.encode(&rows[1]), encoder.encode(&other));
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let mut mt = MersenneTwister64::new(42);
+        let columns = [
+            Column::numeric("x", ScalarFit::new(8)).with_missing(MissingPolicy::Impute),
+            Column::categorical("c", &["a", "b", "?"]).with_missing(MissingPolicy::Impute),
+            Column::boolean("skip"),
+            Column::boolean("vector").with_missing(MissingPolicy::Vector),
+        ];
+        // "?" is a category of c, but a missing value elsewhere
+        let row = parse_row(&columns, &["NA", "?", "", "?"]).unwrap();
+        assert_eq!(row, vec![None, Some(2.0), None, None]);
+
+        let rows = vec![
+            vec![Some(1.0), Some(1.0), Some(1.0), Some(1.0)],
+            vec![Some(2.0), Some(1.0), Some(0.0), Some(0.0)],
+            vec![Some(9.0), Some(0.0), None, Some(1.0)],
+        ];
+        let encoder =
+            TabularEncoder::<RealHDV<64>>::from_columns(&columns, &rows, &mut mt).unwrap();
+        assert!(matches!(encoder.field_missing[0], Missing::Impute(x) if x == 2.0));
+        assert!(matches!(encoder.field_missing[1], Missing::Impute(x) if x == 1.0));
+        assert!(matches!(encoder.field_missing[2], Missing::Skip));
+        assert!(matches!(encoder.field_missing[3], Missing::Vector(_)));
+
+        // imputed values encode like the median / mode
+        let imputed = encoder.encode(&[None, None, Some(1.0), Some(1.0)]);
+        let explicit = encoder.encode(&[Some(2.0), Some(1.0), Some(1.0), Some(1.0)]);
+        assert_eq!(imputed, explicit);
+
+        // the missing vector is neither of the values
+        let missing = encoder.encode(&[Some(2.0), Some(1.0), Some(1.0), None]);
+        assert_ne!(missing, explicit);
+        assert_ne!(
+            missing,
+            encoder.encode(&[Some(2.0), Some(1.0), Some(1.0), Some(0.0)])
+        );
+
+        // a column without training values has nothing to impute - or fit a range to
+        let fixed =
+            [Column::numeric("x", ScalarFit::fixed(0.0, 1.0, 4))
+                .with_missing(MissingPolicy::Impute)];
+        let encoder =
+            TabularEncoder::<RealHDV<64>>::from_columns(&fixed, &[[None]], &mut mt).unwrap();
+        assert!(matches!(encoder.field_missing[0], Missing::Vector(_)));
+        encoder.encode(&[None]);
+        let empty = [[None, None, Some(1.0), Some(1.0)]];
+        assert!(TabularEncoder::<RealHDV<64>>::from_columns(&columns, &empty, &mut mt).is_err());
+
+        // the same for fitting numeric rows - NaN is missing
+        let fits = [ScalarFit::new(4); 2];
+        let rows = [[f32::NAN, 1.0], [f32::NAN, 2.0]];
+        assert!(TabularEncoder::<RealHDV<64>>::fit(&rows, &fits, &mut mt).is_err());
+        assert!(TabularEncoder::<RealHDV<64>>::fit(&[[1.0]], &fits, &mut mt).is_err());
+        assert!(TabularEncoder::<RealHDV<64>>::fit(&[[1.0, 2.0]], &fits, &mut mt).is_ok());
+    }
+
+    #[test]
+    fn test_hashed_column() {
+        let mut mt = MersenneTwister64::new(42);
+        let columns = [Column::hashed("country").with_missing(MissingPolicy::Impute)];
+        let rows: Vec<Vec<Option<f32>>> = ["Canada", "Peru", "Canada", "?"]
+            .iter()
+            .map(|t| parse_row(&columns, &[t]).unwrap())
+            .collect();
+        assert_eq!(rows[0], rows[2]);
+        assert_eq!(rows[3], vec![None]);
+        let encoder =
+            TabularEncoder::<RealHDV<64>>::from_columns(&columns, &rows, &mut mt).unwrap();
+        assert_eq!(encoder.encode(&rows[3]), encoder.encode(&rows[0]));
+
+        // a country not in the training rows still gets its own vector
+        let unseen = encoder.encode(&parse_row(&columns, &["Iceland"]).unwrap());
+        assert!(unseen.distance(&encoder.encode(&rows[0])) > 0.1);
+        assert!(unseen.distance(&encoder.encode(&rows[1])) > 0.1);
+    }
+
+    fn check_fwht_projection<T: HyperVector + FromSpectrum>() {
+        let mut mt = MersenneTwister64::new(42);
+        let encoder = FwhtEncoder::<T, 5>::new(&mut mt);
+        let a = encoder.encode(&[1.0, 0.5, -0.2, 0.3, 0.9]);
+        let near = encoder.encode(&[1.0, 0.45, -0.2, 0.35, 0.9]);
+        let far = encoder.encode(&[-0.8, 0.1, 0.7, -0.6, 0.2]);
+        assert!(a.distance(&encoder.encode(&[1.0, 0.5, -0.2, 0.3, 0.9])) < 1e-4);
+        assert!(a.distance(&near) < a.distance(&far));
+        assert!(a.distance(&far) > 0.2);
+    }
+
+    #[test]
+    fn test_fwht_all_types() {
+        check_fwht_projection::<Binary<16>>();
+        check_fwht_projection::<Binary<16, true>>();
+        check_fwht_projection::<RealHDV<1000>>();
+        check_fwht_projection::<ComplexHDV<1000>>();
+        check_fwht_projection::<Modular<1000>>();
+    }
+
+    #[test]
+    fn test_fwht_more_features_than_dims() {
+        let mut mt = MersenneTwister64::new(42);
+        let encoder = FwhtEncoder::<Binary<1>, 100>::new(&mut mt);
+        assert_eq!(encoder.rows.len(), 64);
+        let features: [f32; 100] = std::array::from_fn(|i| (i as f32).sin());
+        let a = encoder.encode(&features);
+        assert_eq!(a, encoder.encode(&features));
+    }
+
+    fn check_rff_kernel<T: HyperVector + FromPhases>(kernel: Kernel) {
+        let mut mt = MersenneTwister64::new(42);
+        let encoder = RffEncoder::<T, 3>::new(kernel, &mut mt);
+        let a = encoder.encode(&[0.0, 0.0, 0.0]);
+        let near = encoder.encode(&[0.1, 0.0, -0.1]);
+        let mid = encoder.encode(&[0.5, 0.5, 0.0]);
+        let far = encoder.encode(&[5.0, -5.0, 5.0]);
+        assert!(a.distance(&near) < a.distance(&mid));
+        assert!(a.distance(&mid) < a.distance(&far));
+    }
+
+    #[test]
+    fn test_rff_all_types() {
+        for kernel in [
+            Kernel::Rbf { bandwidth: 1.0 },
+            Kernel::Laplacian { bandwidth: 1.0 },
+        ] {
+            check_rff_kernel::<Binary<32>>(kernel);
+            check_rff_kernel::<Binary<32, true>>(kernel);
+            check_rff_kernel::<RealHDV<2048>>(kernel);
+            check_rff_kernel::<ComplexHDV<2048>>(kernel);
+            check_rff_kernel::<Modular<2048>>(kernel);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bandwidth must be > 0")]
+    fn test_rff_zero_bandwidth() {
+        let kernel = Kernel::Rbf { bandwidth: 0.0 };
+        RffEncoder::<Binary<4>, 3>::new(kernel, &mut MersenneTwister64::new(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "bandwidth must be > 0")]
+    fn test_rff_negative_bandwidth() {
+        let kernel = Kernel::Laplacian { bandwidth: -1.0 };
+        RffEncoder::<Binary<4>, 3>::new(kernel, &mut MersenneTwister64::new(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "bandwidth must be > 0")]
+    fn test_rff_nan_bandwidth() {
+        let kernel = Kernel::Rbf {
+            bandwidth: f32::NAN,
+        };
+        RffEncoder::<Binary<4>, 3>::new(kernel, &mut MersenneTwister64::new(1));
+    }
+
+    #[test]
+    fn test_encoder_combinators() {
+        let mut mt = MersenneTwister64::new(42);
+        let key = Binary::<16>::random(&mut mt);
+        let levels = ScalarEncoder::<Binary<16>>::new(0.0, 10.0, 11, &mut mt);
+        let expected = levels.basis[3].bind(&key).permute(5);
+
+        let encoder = levels.bound_to(key.clone()).permuted(5);
+        assert_eq!(encoder.encode(&3.0), expected);
+        let batch = encoder.encode_batch(&[1.0, 3.0, 9.0]);
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch[1], expected);
+
+        // all parts agree on index 0, so the bundle is exactly that vector
+        let categories = CategoricalEncoder::<Binary<16>>::new(4, &mut mt);
+        let a = categories.values[0].clone();
+        let bundle = WeightedBundle::new()
+            .with(
+                CategoricalEncoder {
+                    values: vec![a.clone()],
+                },
+                1.0,
+            )
+            .with(categories, 1.0)
+            .with(
+                CategoricalEncoder {
+                    values: vec![a.clone()],
+                },
+                1.0,
+            );
+        assert_eq!(bundle.encode(&0), a);
+    }
+
     #[test]
     fn test_clamping() {
         let mut mt = MersenneTwister64::new(42);
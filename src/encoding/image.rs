@@ -0,0 +1,341 @@
+use crate::encoding::{Encoder, ScalarEncoder};
+use crate::types::traits::{Accumulator, HyperVector};
+use rand::Rng;
+
+// ── ImageEncoder ────────────────────────────────────────────────────────────────────
+//
+// Encodes a grayscale image (row-major u8 pixels, width x height) as a weighted bundle
+// of position-bound features:
+// - pixel bag: position ⊗ intensity, weighted by intensity
+// - edges: position ⊗ edge key, where a 3x3 kernel response exceeds its threshold
+// - patches: position ⊗ local n-gram of the quantized pixels in a small window
+// - thinning: position ⊗ skeleton key for the Zhang-Suen skeleton pixels
+// At least one feature must be enabled.
+
+/// A 3x3 convolution kernel, rows top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeKernel {
+    pub weights: [[i16; 3]; 3],
+    /// minimum absolute response for a pixel to count as an edge
+    pub threshold: i16,
+}
+
+impl EdgeKernel {
+    pub fn new(weights: [[i16; 3]; 3], threshold: i16) -> Self {
+        Self { weights, threshold }
+    }
+
+    /// Sobel Gx: (right side) - (left side)
+    pub fn sobel_horizontal(threshold: i16) -> Self {
+        Self::new([[-1, 0, 1], [-2, 0, 2], [-1, 0, 1]], threshold)
+    }
+
+    /// Sobel Gy: (bottom side) - (top side)
+    pub fn sobel_vertical(threshold: i16) -> Self {
+        Self::new([[-1, -2, -1], [0, 0, 0], [1, 2, 1]], threshold)
+    }
+
+    /// Diagonal edge (/)
+    pub fn sobel_diagonal1(threshold: i16) -> Self {
+        Self::new([[0, 1, 2], [-1, 0, 1], [-2, -1, 0]], threshold)
+    }
+
+    /// Diagonal edge (\)
+    pub fn sobel_diagonal2(threshold: i16) -> Self {
+        Self::new([[2, 1, 0], [1, 0, -1], [0, -1, -2]], threshold)
+    }
+
+    /// Response at (x, y) - the caller keeps the 3x3 window inside the image.
+    /// Summed in i32, as i16 weights times 255 overflow i16.
+    fn response(&self, pixels: &[u8], width: usize, x: usize, y: usize) -> i32 {
+        let mut sum = 0;
+        for (dy, row) in self.weights.iter().enumerate() {
+            for (dx, &w) in row.iter().enumerate() {
+                sum += w as i32 * pixels[(y + dy - 1) * width + (x + dx - 1)] as i32;
+            }
+        }
+        sum
+    }
+
+    /// Largest possible absolute response - all 255 under the positive or the
+    /// negative weights, whichever side is larger
+    fn max_response(&self) -> f64 {
+        let weights = self.weights.iter().flatten().map(|&w| w as i32);
+        let positive: i32 = weights.clone().filter(|&w| w > 0).sum();
+        let negative: i32 = weights.filter(|&w| w < 0).sum();
+        (positive.max(-negative) as f64 * 255.0).max(1.0)
+    }
+}
+
+struct Patches<H: HyperVector> {
+    size: usize,
+    stride: usize,
+    levels: ScalarEncoder<H>,
+}
+
+pub struct ImageEncoder<H: HyperVector> {
+    pub width: usize,
+    pub height: usize,
+    positions: Vec<H>,             // one per pixel
+    intensities: ScalarEncoder<H>, // 256 gray levels (0-255)
+    pixel_bag: Option<u8>,         // threshold
+    edges: Vec<(EdgeKernel, H)>,
+    pub edge_weight: f64,
+    patches: Option<Patches<H>>,
+    thinning: Option<(u8, H)>, // binarization threshold, skeleton key
+}
+
+impl<H: HyperVector> ImageEncoder<H> {
+    pub fn new(width: usize, height: usize, rng: &mut impl Rng) -> Self {
+        let positions = (0..width * height).map(|_| H::random(rng)).collect();
+        let intensities = ScalarEncoder::new(0.0, 255.0, 256, rng);
+
+        Self {
+            width,
+            height,
+            positions,
+            intensities,
+            pixel_bag: None,
+            edges: Vec::new(),
+            edge_weight: 4.0,
+            patches: None,
+            thinning: None,
+        }
+    }
+
+    /// Pixels brighter than `threshold`, weighted by intensity
+    pub fn with_pixel_bag(mut self, threshold: u8) -> Self {
+        self.pixel_bag = Some(threshold);
+        self
+    }
+
+    pub fn with_edge(mut self, kernel: EdgeKernel, rng: &mut impl Rng) -> Self {
+        self.edges.push((kernel, H::random(rng)));
+        self
+    }
+
+    /// Horizontal, vertical and both diagonal Sobel kernels
+    pub fn with_sobel_edges(self, threshold: i16, rng: &mut impl Rng) -> Self {
+        self.with_edge(EdgeKernel::sobel_horizontal(threshold), rng)
+            .with_edge(EdgeKernel::sobel_vertical(threshold), rng)
+            .with_edge(EdgeKernel::sobel_diagonal1(threshold), rng)
+            .with_edge(EdgeKernel::sobel_diagonal2(threshold), rng)
+    }
+
+    /// Edge feature weight relative to a full intensity pixel
+    pub fn with_edge_weight(mut self, weight: f64) -> Self {
+        self.edge_weight = weight;
+        self
+    }
+
+    /// size x size windows every `stride` pixels, pixels quantized to `levels` gray levels.
+    /// A window is the n-gram of its pixel levels in row-major order; background-only
+    /// windows are left out.
+    pub fn with_patches(
+        mut self,
+        size: usize,
+        stride: usize,
+        levels: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        assert!(size >= 1 && stride >= 1 && levels >= 2);
+        let levels = ScalarEncoder::new(0.0, 255.0, levels, rng);
+        self.patches = Some(Patches {
+            size,
+            stride,
+            levels,
+        });
+        self
+    }
+
+    /// Skeleton of the image binarized at `threshold`
+    pub fn with_thinning(mut self, threshold: u8, rng: &mut impl Rng) -> Self {
+        self.thinning = Some((threshold, H::random(rng)));
+        self
+    }
+
+    fn add_pixel_bag(&self, pixels: &[u8], threshold: u8, acc: &mut H::Accumulator) {
+        for (i, &intensity) in pixels.iter().enumerate() {
+            if intensity > threshold {
+                let intensity_hdv = self.intensities.encode(intensity as f32);
+                let pixel_hdv = self.positions[i].bind(intensity_hdv);
+                acc.add(&pixel_hdv, intensity as f64 / 255.0);
+            }
+        }
+    }
+
+    fn add_edges(&self, pixels: &[u8], acc: &mut H::Accumulator) {
+        let (width, height) = (self.width, self.height);
+        for y in 1..height.saturating_sub(1) {
+            for x in 1..width.saturating_sub(1) {
+                for (kernel, key) in &self.edges {
+                    let diff = kernel.response(pixels, width, x, y).abs();
+                    if diff > kernel.threshold as i32 {
+                        let feature_hdv = self.positions[y * width + x].bind(key);
+                        let magnitude = diff as f64 / kernel.max_response();
+                        acc.add(&feature_hdv, self.edge_weight * magnitude);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_patches(&self, pixels: &[u8], patches: &Patches<H>, acc: &mut H::Accumulator) {
+        let (width, height, size) = (self.width, self.height, patches.size);
+        if size > width || size > height {
+            return;
+        }
+        for y in (0..=height - size).step_by(patches.stride) {
+            for x in (0..=width - size).step_by(patches.stride) {
+                let mut ngram = H::ident();
+                let mut background = true;
+                for dy in 0..size {
+                    for dx in 0..size {
+                        let p = pixels[(y + dy) * width + x + dx] as f32;
+                        let level = patches.levels.level(p);
+                        background &= level == 0;
+                        ngram = ngram.bind(&patches.levels.basis[level].permute(dy * size + dx));
+                    }
+                }
+                if !background {
+                    acc.add(&ngram.bind(&self.positions[y * width + x]), 1.0);
+                }
+            }
+        }
+    }
+
+    fn add_thinned(&self, pixels: &[u8], threshold: u8, key: &H, acc: &mut H::Accumulator) {
+        let mut bits: Vec<bool> = pixels.iter().map(|&p| p > threshold).collect();
+        zhang_suen::thinning(&mut bits, self.width);
+
+        // Skeleton pixels are binary, so we use a full weight of 1.0
+        for (i, _) in bits.iter().enumerate().filter(|&(_, &b)| b) {
+            acc.add(&self.positions[i].bind(key), 1.0);
+        }
+    }
+}
+
+/// Input: row-major grayscale pixels, `width * height` of them
+impl<H: HyperVector> Encoder<[u8], H> for ImageEncoder<H> {
+    fn encode(&self, pixels: &[u8]) -> H {
+        assert_eq!(pixels.len(), self.width * self.height, "image size");
+        let mut acc = H::Accumulator::new();
+        if let Some(threshold) = self.pixel_bag {
+            self.add_pixel_bag(pixels, threshold, &mut acc);
+        }
+        if !self.edges.is_empty() {
+            self.add_edges(pixels, &mut acc);
+        }
+        if let Some(patches) = &self.patches {
+            self.add_patches(pixels, patches, &mut acc);
+        }
+        if let Some((threshold, key)) = &self.thinning {
+            self.add_thinned(pixels, *threshold, key, &mut acc);
+        }
+        acc.finalize()
+    }
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod image_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use crate::types::real::RealHDV;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    // width x height, left half black, right half white
+    fn step_image(width: usize, height: usize) -> Vec<u8> {
+        (0..width * height)
+            .map(|i| if i % width < width / 2 { 0 } else { 255 })
+            .collect()
+    }
+
+    #[test]
+    fn test_edge_kernels() {
+        let pixels = step_image(6, 4);
+        let horizontal = EdgeKernel::sobel_horizontal(100);
+        let vertical = EdgeKernel::sobel_vertical(100);
+        assert_eq!(horizontal.response(&pixels, 6, 2, 1), 1020);
+        assert_eq!(horizontal.response(&pixels, 6, 1, 1), 0);
+        assert_eq!(vertical.response(&pixels, 6, 2, 1), 0);
+        assert_eq!(horizontal.max_response(), 1020.0);
+
+        // large weights do not overflow, and a dominant negative side sets the maximum
+        let kernel = EdgeKernel::new([[-20, -20, -20], [0, 0, 0], [0, 1, 0]], 100);
+        let white = vec![255; 9];
+        assert_eq!(kernel.response(&white, 3, 1, 1), -59 * 255);
+        assert_eq!(kernel.max_response(), 60.0 * 255.0);
+    }
+
+    #[test]
+    fn test_patches() {
+        let mut mt = MersenneTwister64::new(7);
+        let empty = <Binary<8> as HyperVector>::Accumulator::new().finalize();
+        let encoder = ImageEncoder::<Binary<8>>::new(6, 6, &mut mt).with_patches(3, 3, 2, &mut mt);
+        // background-only windows are left out
+        assert_eq!(encoder.encode(&[0; 36][..]), empty);
+
+        // one white pixel - the same window, at a different position in it, or in another window
+        let dot = |x: usize, y: usize| -> Vec<u8> {
+            (0..36)
+                .map(|i| if i == y * 6 + x { 255 } else { 0 })
+                .collect()
+        };
+        let a = encoder.encode(&dot(1, 1)[..]);
+        assert_ne!(a, empty);
+        assert_eq!(a, encoder.encode(&dot(1, 1)[..]));
+        assert!(a.distance(&encoder.encode(&dot(2, 1)[..])) > 0.2);
+        assert!(a.distance(&encoder.encode(&dot(4, 1)[..])) > 0.2);
+
+        // windows larger than the image
+        let encoder = ImageEncoder::<Binary<8>>::new(2, 2, &mut mt).with_patches(3, 1, 2, &mut mt);
+        assert_eq!(encoder.encode(&[255; 4][..]), empty);
+    }
+
+    #[test]
+    fn test_thinning() {
+        let mut mt = MersenneTwister64::new(9);
+        let (width, height) = (12, 7);
+        let encoder =
+            ImageEncoder::<Binary<8>>::new(width, height, &mut mt).with_thinning(127, &mut mt);
+        // a 3 pixel wide bar
+        let bar: Vec<u8> = (0..width * height)
+            .map(|i| {
+                if (2..5).contains(&(i / width)) && (1..11).contains(&(i % width)) {
+                    255
+                } else {
+                    0
+                }
+            })
+            .collect();
+        let mut skeleton: Vec<bool> = bar.iter().map(|&p| p > 127).collect();
+        zhang_suen::thinning(&mut skeleton, width);
+        assert!(skeleton.iter().any(|&b| b));
+        assert!(skeleton.iter().zip(&bar).all(|(&s, &p)| !s || p == 255));
+
+        // only the skeleton pixels are encoded - a skeleton thins to itself
+        let skeleton: Vec<u8> = skeleton.iter().map(|&b| if b { 255 } else { 0 }).collect();
+        let encoded = encoder.encode(&bar[..]);
+        assert_eq!(encoded, encoder.encode(&skeleton[..]));
+        // below the binarization threshold nothing is left
+        let dim: Vec<u8> = bar.iter().map(|&p| p / 2).collect();
+        assert_ne!(encoded, encoder.encode(&dim[..]));
+    }
+
+    #[test]
+    fn test_non_square_image() {
+        let mut mt = MersenneTwister64::new(42);
+        let encoder = ImageEncoder::<RealHDV<256>>::new(12, 5, &mut mt)
+            .with_pixel_bag(0)
+            .with_sobel_edges(100, &mut mt)
+            .with_patches(3, 2, 2, &mut mt);
+        let step = encoder.encode(&step_image(12, 5)[..]);
+        let flipped: Vec<u8> = step_image(12, 5).iter().map(|p| 255 - p).collect();
+        let other = encoder.encode(&flipped[..]);
+        assert!(step.distance(&other) > 0.2);
+        // RealHDV bundling is deterministic
+        assert_eq!(encoder.encode_batch(&[&step_image(12, 5)[..]])[0], step);
+    }
+}
@@ -1,8 +1,10 @@
+use rayon::prelude::*;
 use std::fs::File;
 use std::io::{BufWriter, Read, Write};
 
 pub mod datasets;
 pub mod encoding;
+pub mod metrics;
 pub mod trainer;
 pub mod types;
 
@@ -93,3 +95,12 @@ pub fn nearest_two<T: HyperVector>(query: &T, candidates: &[T]) -> ((usize, f32)
     }
     (first, second)
 }
+
+/// Pairwise distances of the vectors, rows computed in parallel - n² floats, so for
+/// up to a few thousand vectors.
+pub fn distance_matrix<T: HyperVector + Sync>(vectors: &[T]) -> Vec<Vec<f32>> {
+    vectors
+        .par_iter()
+        .map(|a| vectors.iter().map(|b| a.distance(b)).collect())
+        .collect()
+}
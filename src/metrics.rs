@@ -0,0 +1,824 @@
+use crate::{HyperVector, UnitAccumulator};
+use rayon::prelude::*;
+use std::fmt;
+use std::fmt::Write;
+
+// ── Classification metrics ────────────────────────────────────────────────────
+//
+// Everything is derived from the confusion matrix - row = true class, column =
+// predicted class. Ratios that are undefined (0 / 0), e.g. the precision of a
+// class that is never predicted, are reported as 0.
+
+/// Precision, recall and F1 of a class - or their average over the classes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClassMetrics {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    /// Number of samples of the class - of all classes for averages
+    pub support: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    n_classes: usize,
+    counts: Vec<usize>, // row major, n_classes * n_classes
+}
+
+impl ConfusionMatrix {
+    pub fn new(n_classes: usize) -> Self {
+        Self {
+            n_classes,
+            counts: vec![0; n_classes * n_classes],
+        }
+    }
+
+    pub fn from_predictions<P, L>(predictions: &[P], labels: &[L], n_classes: usize) -> Self
+    where
+        P: Into<usize> + Copy,
+        L: Into<usize> + Copy,
+    {
+        assert_eq!(predictions.len(), labels.len());
+        let mut m = Self::new(n_classes);
+        for (&p, &l) in predictions.iter().zip(labels) {
+            m.add(l.into(), p.into());
+        }
+        m
+    }
+
+    pub fn add(&mut self, truth: usize, predicted: usize) {
+        assert!(truth < self.n_classes && predicted < self.n_classes);
+        self.counts[truth * self.n_classes + predicted] += 1;
+    }
+
+    /// Number of samples of class `truth` predicted as `predicted`
+    pub fn get(&self, truth: usize, predicted: usize) -> usize {
+        self.counts[truth * self.n_classes + predicted]
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.n_classes
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn correct(&self) -> usize {
+        (0..self.n_classes).map(|c| self.get(c, c)).sum()
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct(), self.total())
+    }
+
+    /// Number of samples of the class
+    pub fn support(&self, class: usize) -> usize {
+        (0..self.n_classes).map(|p| self.get(class, p)).sum()
+    }
+
+    /// Number of samples predicted as the class
+    pub fn predicted(&self, class: usize) -> usize {
+        (0..self.n_classes).map(|t| self.get(t, class)).sum()
+    }
+
+    pub fn precision(&self, class: usize) -> f64 {
+        ratio(self.get(class, class), self.predicted(class))
+    }
+
+    pub fn recall(&self, class: usize) -> f64 {
+        ratio(self.get(class, class), self.support(class))
+    }
+
+    pub fn f1(&self, class: usize) -> f64 {
+        let (p, r) = (self.precision(class), self.recall(class));
+        if p + r > 0.0 {
+            2.0 * p * r / (p + r)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn class_metrics(&self) -> Vec<ClassMetrics> {
+        (0..self.n_classes)
+            .map(|c| ClassMetrics {
+                precision: self.precision(c),
+                recall: self.recall(c),
+                f1: self.f1(c),
+                support: self.support(c),
+            })
+            .collect()
+    }
+
+    /// Unweighted mean over the classes - every class counts the same
+    pub fn macro_avg(&self) -> ClassMetrics {
+        self.average(|_| 1.0 / self.n_classes as f64)
+    }
+
+    /// Mean over the classes weighted by support
+    pub fn weighted_avg(&self) -> ClassMetrics {
+        let total = self.total();
+        self.average(|m| ratio(m.support, total))
+    }
+
+    fn average(&self, weight: impl Fn(&ClassMetrics) -> f64) -> ClassMetrics {
+        let mut avg = ClassMetrics {
+            precision: 0.0,
+            recall: 0.0,
+            f1: 0.0,
+            support: self.total(),
+        };
+        for m in self.class_metrics() {
+            let w = weight(&m);
+            avg.precision += w * m.precision;
+            avg.recall += w * m.recall;
+            avg.f1 += w * m.f1;
+        }
+        avg
+    }
+
+    /// Mean recall over the classes that occur - the accuracy as if the classes
+    /// were balanced
+    pub fn balanced_accuracy(&self) -> f64 {
+        let recalls: Vec<f64> = (0..self.n_classes)
+            .filter(|&c| self.support(c) > 0)
+            .map(|c| self.recall(c))
+            .collect();
+        if recalls.is_empty() {
+            0.0
+        } else {
+            recalls.iter().sum::<f64>() / recalls.len() as f64
+        }
+    }
+
+    /// Agreement of predictions and labels beyond chance: 1 perfect, 0 chance level
+    pub fn cohen_kappa(&self) -> f64 {
+        let total = self.total() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        let observed = self.correct() as f64 / total;
+        let expected: f64 = (0..self.n_classes)
+            .map(|c| self.support(c) as f64 * self.predicted(c) as f64)
+            .sum::<f64>()
+            / (total * total);
+        if expected < 1.0 {
+            (observed - expected) / (1.0 - expected)
+        } else {
+            0.0
+        }
+    }
+
+    /// Per-class precision, recall, F1 and support with averages, accuracy,
+    /// balanced accuracy and kappa. `names` of the classes - class indices if empty.
+    pub fn report<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let names = self.names(names);
+        let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(12);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>width$} {:>9} {:>9} {:>9} {:>9}",
+            "", "precision", "recall", "f1", "support"
+        );
+        let mut row = |name: &str, m: &ClassMetrics| {
+            let _ = writeln!(
+                out,
+                "{name:>width$} {:9.4} {:9.4} {:9.4} {:9}",
+                m.precision, m.recall, m.f1, m.support
+            );
+        };
+        for (name, m) in names.iter().zip(self.class_metrics()) {
+            row(name, &m);
+        }
+        row("macro avg", &self.macro_avg());
+        row("weighted avg", &self.weighted_avg());
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{:>width$} {:9.4}", "accuracy", self.accuracy());
+        let _ = writeln!(
+            out,
+            "{:>width$} {:9.4}",
+            "balanced acc",
+            self.balanced_accuracy()
+        );
+        let _ = write!(out, "{:>width$} {:9.4}", "kappa", self.cohen_kappa());
+        out
+    }
+
+    /// The report as CSV: class,precision,recall,f1,support - with the averages as
+    /// rows "macro avg" and "weighted avg"
+    pub fn report_csv<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let mut out = String::from("class,precision,recall,f1,support\n");
+        let mut row = |name: &str, m: &ClassMetrics| {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{}",
+                csv_field(name),
+                m.precision,
+                m.recall,
+                m.f1,
+                m.support
+            );
+        };
+        for (name, m) in self.names(names).iter().zip(self.class_metrics()) {
+            row(name, &m);
+        }
+        row("macro avg", &self.macro_avg());
+        row("weighted avg", &self.weighted_avg());
+        out
+    }
+
+    /// The matrix as CSV, a header row with the predicted classes and a row per
+    /// true class
+    pub fn to_csv<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let names = self.names(names);
+        let mut out = String::from("true\\pred");
+        for name in &names {
+            let _ = write!(out, ",{}", csv_field(name));
+        }
+        out.push('\n');
+        for (t, name) in names.iter().enumerate() {
+            out.push_str(&csv_field(name));
+            for p in 0..self.n_classes {
+                let _ = write!(out, ",{}", self.get(t, p));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn names<S: AsRef<str>>(&self, names: &[S]) -> Vec<String> {
+        if names.is_empty() {
+            (0..self.n_classes).map(|c| c.to_string()).collect()
+        } else {
+            assert_eq!(names.len(), self.n_classes, "one name per class");
+            names.iter().map(|n| n.as_ref().to_string()).collect()
+        }
+    }
+}
+
+/// The matrix with class indices, row = true class, column = predicted class
+impl fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .counts
+            .iter()
+            .max()
+            .unwrap_or(&0)
+            .to_string()
+            .len()
+            .max(5)
+            + 1;
+        write!(f, "true\\pred")?;
+        for p in 0..self.n_classes {
+            write!(f, "{p:width$}")?;
+        }
+        for t in 0..self.n_classes {
+            write!(f, "\n{t:8} ")?;
+            for p in 0..self.n_classes {
+                write!(f, "{:width$}", self.get(t, p))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Area under the ROC curve of scores (higher = more likely positive): the
+/// probability that a random positive scores above a random negative, ties
+/// counting half. None unless there are positives and negatives.
+pub fn roc_auc(scores: &[f32], positive: &[bool]) -> Option<f64> {
+    assert_eq!(scores.len(), positive.len());
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+
+    // Mann-Whitney U from the ranks, ties get their mean rank
+    let mut rank_sum = 0.0;
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && scores[order[j + 1]] == scores[order[i]] {
+            j += 1;
+        }
+        let mean_rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum += mean_rank * order[i..=j].iter().filter(|&&k| positive[k]).count() as f64;
+        i = j + 1;
+    }
+    let n_pos = positive.iter().filter(|&&p| p).count() as f64;
+    let n_neg = positive.len() as f64 - n_pos;
+    if n_pos == 0.0 || n_neg == 0.0 {
+        return None;
+    }
+    Some((rank_sum - n_pos * (n_pos + 1.0) / 2.0) / (n_pos * n_neg))
+}
+
+/// One-vs-rest ROC-AUC per class from per-sample class scores - e.g. from
+/// Classifier::scores. None for classes without positives or negatives.
+pub fn roc_auc_ovr<S, L>(scores: &[S], labels: &[L], n_classes: usize) -> Vec<Option<f64>>
+where
+    S: AsRef<[f32]>,
+    L: Into<usize> + Copy,
+{
+    assert_eq!(scores.len(), labels.len());
+    (0..n_classes)
+        .map(|c| {
+            let class_scores: Vec<f32> = scores.iter().map(|s| s.as_ref()[c]).collect();
+            let positive: Vec<bool> = labels.iter().map(|&l| l.into() == c).collect();
+            roc_auc(&class_scores, &positive)
+        })
+        .collect()
+}
+
+/// Mean of the defined per-class AUCs
+pub fn macro_roc_auc(aucs: &[Option<f64>]) -> Option<f64> {
+    let defined: Vec<f64> = aucs.iter().flatten().copied().collect();
+    if defined.is_empty() {
+        None
+    } else {
+        Some(defined.iter().sum::<f64>() / defined.len() as f64)
+    }
+}
+
+// ── Multi-label metrics ───────────────────────────────────────────────────────
+//
+// A sample has a set of labels, given as label indices in any order. Every
+// (sample, label) pair is a yes/no decision: micro averages pool the decisions of
+// all labels, macro averages weigh every label the same.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiLabelMetrics {
+    n_labels: usize,
+    samples: usize,
+    // samples predicted with exactly their label set
+    exact: usize,
+    true_pos: Vec<usize>,
+    false_pos: Vec<usize>,
+    false_neg: Vec<usize>,
+}
+
+impl MultiLabelMetrics {
+    pub fn from_predictions<P, L>(predictions: &[P], labels: &[L], n_labels: usize) -> Self
+    where
+        P: AsRef<[usize]>,
+        L: AsRef<[usize]>,
+    {
+        assert_eq!(predictions.len(), labels.len());
+        let mut m = Self {
+            n_labels,
+            samples: labels.len(),
+            exact: 0,
+            true_pos: vec![0; n_labels],
+            false_pos: vec![0; n_labels],
+            false_neg: vec![0; n_labels],
+        };
+        for (p, l) in predictions.iter().zip(labels) {
+            let (p, l) = (
+                label_set(p.as_ref(), n_labels),
+                label_set(l.as_ref(), n_labels),
+            );
+            if p == l {
+                m.exact += 1;
+            }
+            for (j, (&p, &l)) in p.iter().zip(&l).enumerate() {
+                match (p, l) {
+                    (true, true) => m.true_pos[j] += 1,
+                    (true, false) => m.false_pos[j] += 1,
+                    (false, true) => m.false_neg[j] += 1,
+                    (false, false) => {}
+                }
+            }
+        }
+        m
+    }
+
+    pub fn n_labels(&self) -> usize {
+        self.n_labels
+    }
+
+    /// Fraction of the (sample, label) decisions that are wrong - lower is better
+    pub fn hamming_loss(&self) -> f64 {
+        let wrong: usize = self.false_pos.iter().chain(&self.false_neg).sum();
+        ratio(wrong, self.samples * self.n_labels)
+    }
+
+    /// Fraction of the samples predicted with exactly their label set
+    pub fn subset_accuracy(&self) -> f64 {
+        ratio(self.exact, self.samples)
+    }
+
+    pub fn label_metrics(&self) -> Vec<ClassMetrics> {
+        (0..self.n_labels)
+            .map(|j| {
+                class_metrics(
+                    self.true_pos[j],
+                    self.false_pos[j],
+                    self.false_neg[j],
+                    self.true_pos[j] + self.false_neg[j],
+                )
+            })
+            .collect()
+    }
+
+    /// Precision, recall and F1 of the decisions of all labels pooled
+    pub fn micro_avg(&self) -> ClassMetrics {
+        let sum = |v: &[usize]| v.iter().sum::<usize>();
+        let (tp, fp, fn_) = (
+            sum(&self.true_pos),
+            sum(&self.false_pos),
+            sum(&self.false_neg),
+        );
+        class_metrics(tp, fp, fn_, tp + fn_)
+    }
+
+    /// Unweighted mean over the labels - every label counts the same
+    pub fn macro_avg(&self) -> ClassMetrics {
+        let labels = self.label_metrics();
+        let n = self.n_labels.max(1) as f64;
+        ClassMetrics {
+            precision: labels.iter().map(|m| m.precision).sum::<f64>() / n,
+            recall: labels.iter().map(|m| m.recall).sum::<f64>() / n,
+            f1: labels.iter().map(|m| m.f1).sum::<f64>() / n,
+            support: labels.iter().map(|m| m.support).sum(),
+        }
+    }
+
+    /// Per-label precision, recall, F1 and support with micro and macro averages,
+    /// Hamming loss and subset accuracy. `names` of the labels - indices if empty.
+    pub fn report<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let names: Vec<String> = if names.is_empty() {
+            (0..self.n_labels).map(|j| j.to_string()).collect()
+        } else {
+            assert_eq!(names.len(), self.n_labels, "one name per label");
+            names.iter().map(|n| n.as_ref().to_string()).collect()
+        };
+        let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(12);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>width$} {:>9} {:>9} {:>9} {:>9}",
+            "", "precision", "recall", "f1", "support"
+        );
+        let mut row = |name: &str, m: &ClassMetrics| {
+            let _ = writeln!(
+                out,
+                "{name:>width$} {:9.4} {:9.4} {:9.4} {:9}",
+                m.precision, m.recall, m.f1, m.support
+            );
+        };
+        for (name, m) in names.iter().zip(self.label_metrics()) {
+            row(name, &m);
+        }
+        row("micro avg", &self.micro_avg());
+        row("macro avg", &self.macro_avg());
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{:>width$} {:9.4}",
+            "hamming loss",
+            self.hamming_loss()
+        );
+        let _ = write!(
+            out,
+            "{:>width$} {:9.4}",
+            "subset acc",
+            self.subset_accuracy()
+        );
+        out
+    }
+}
+
+impl fmt::Display for MultiLabelMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hamming loss {:.4}, subset accuracy {:.2}%, micro F1 {:.4}, macro F1 {:.4}",
+            self.hamming_loss(),
+            100.0 * self.subset_accuracy(),
+            self.micro_avg().f1,
+            self.macro_avg().f1
+        )
+    }
+}
+
+// the label set as one flag per label
+fn label_set(labels: &[usize], n_labels: usize) -> Vec<bool> {
+    let mut set = vec![false; n_labels];
+    for &l in labels {
+        assert!(l < n_labels, "label {l} out of range");
+        set[l] = true;
+    }
+    set
+}
+
+fn class_metrics(
+    true_pos: usize,
+    false_pos: usize,
+    false_neg: usize,
+    support: usize,
+) -> ClassMetrics {
+    let precision = ratio(true_pos, true_pos + false_pos);
+    let recall = ratio(true_pos, true_pos + false_neg);
+    let f1 = if precision + recall > 0.0 {
+        2.0 * precision * recall / (precision + recall)
+    } else {
+        0.0
+    };
+    ClassMetrics {
+        precision,
+        recall,
+        f1,
+        support,
+    }
+}
+
+// ── Regression metrics ────────────────────────────────────────────────────────
+
+/// Errors of predicted values, see trainer::regression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegressionMetrics {
+    /// Mean absolute error
+    pub mae: f64,
+    /// Root mean squared error
+    pub rmse: f64,
+    /// Coefficient of determination: 1 - squared error / variance of the targets.
+    /// 1 for perfect predictions, 0 for predicting the mean target.
+    pub r2: f64,
+    pub count: usize,
+}
+
+impl RegressionMetrics {
+    pub fn new(predictions: &[f32], targets: &[f32]) -> Self {
+        assert!(!targets.is_empty() && predictions.len() == targets.len());
+        let n = targets.len() as f64;
+        let mean = targets.iter().map(|&t| t as f64).sum::<f64>() / n;
+        let (mut abs, mut sq, mut var) = (0.0, 0.0, 0.0);
+        for (&p, &t) in predictions.iter().zip(targets) {
+            let e = p as f64 - t as f64;
+            abs += e.abs();
+            sq += e * e;
+            var += (t as f64 - mean).powi(2);
+        }
+        Self {
+            mae: abs / n,
+            rmse: (sq / n).sqrt(),
+            r2: if var > 0.0 { 1.0 - sq / var } else { 0.0 },
+            count: targets.len(),
+        }
+    }
+}
+
+impl fmt::Display for RegressionMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MAE {:.4}, RMSE {:.4}, R² {:.4} ({} samples)",
+            self.mae, self.rmse, self.r2, self.count
+        )
+    }
+}
+
+// ── Cluster quality ───────────────────────────────────────────────────────────
+//
+// For comparing clusterings and picking the number of clusters. Assignments are
+// cluster indices per sample, as from KMeans::assign or Dendrogram::cut - noise
+// from DBSCAN is left out by the caller. Empty cluster indices are skipped.
+
+/// Silhouette of every sample: (b - a) / max(a, b), with a its mean distance to the
+/// other members of its cluster and b to the members of the nearest other cluster.
+/// 0 for a sample alone in its cluster.
+pub fn silhouette_samples<H: HyperVector + Sync>(data: &[H], assignments: &[usize]) -> Vec<f64> {
+    assert_eq!(data.len(), assignments.len());
+    let sizes = cluster_sizes(assignments);
+    data.par_iter()
+        .zip(assignments)
+        .map(|(v, &c)| {
+            if sizes[c] < 2 {
+                return 0.0;
+            }
+            let mut sums = vec![0.0; sizes.len()];
+            for (w, &cw) in data.iter().zip(assignments) {
+                sums[cw] += v.distance(w) as f64;
+            }
+            let a = sums[c] / (sizes[c] - 1) as f64;
+            let b = (0..sizes.len())
+                .filter(|&o| o != c && sizes[o] > 0)
+                .map(|o| sums[o] / sizes[o] as f64)
+                .fold(f64::INFINITY, f64::min);
+            if a.max(b) > 0.0 {
+                (b - a) / a.max(b)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Mean silhouette, in [-1, 1] - higher is better. None with fewer than 2 clusters.
+pub fn silhouette<H: HyperVector + Sync>(data: &[H], assignments: &[usize]) -> Option<f64> {
+    if cluster_sizes(assignments)
+        .iter()
+        .filter(|&&s| s > 0)
+        .count()
+        < 2
+    {
+        return None;
+    }
+    let s = silhouette_samples(data, assignments);
+    Some(s.iter().sum::<f64>() / s.len() as f64)
+}
+
+/// Davies-Bouldin index: the mean over the clusters of the largest
+/// (s_i + s_j) / d(c_i, c_j) over the other clusters, with c the bundle of a cluster
+/// and s the mean distance of its members to it. Lower is better, 0 at best.
+/// None with fewer than 2 clusters.
+pub fn davies_bouldin<H: HyperVector + Sync>(data: &[H], assignments: &[usize]) -> Option<f64> {
+    assert_eq!(data.len(), assignments.len());
+    let sizes = cluster_sizes(assignments);
+    let mut accumulators: Vec<H::UnitAccumulator> = (0..sizes.len())
+        .map(|_| H::UnitAccumulator::default())
+        .collect();
+    for (v, &c) in data.iter().zip(assignments) {
+        accumulators[c].add(v);
+    }
+    let clusters: Vec<usize> = (0..sizes.len()).filter(|&c| sizes[c] > 0).collect();
+    if clusters.len() < 2 {
+        return None;
+    }
+    let centroids: Vec<H> = clusters
+        .iter()
+        .map(|&c| accumulators[c].finalize())
+        .collect();
+    let mut scatter = vec![0.0; sizes.len()];
+    for (v, &c) in data.iter().zip(assignments) {
+        let i = clusters.binary_search(&c).unwrap();
+        scatter[i] += v.distance(&centroids[i]) as f64 / sizes[c] as f64;
+    }
+
+    let worst = (0..clusters.len()).map(|i| {
+        (0..clusters.len())
+            .filter(|&j| j != i)
+            .map(|j| {
+                // coinciding centroids do not count, as in scikit-learn
+                let d = centroids[i].distance(&centroids[j]) as f64;
+                if d > 0.0 {
+                    (scatter[i] + scatter[j]) / d
+                } else {
+                    0.0
+                }
+            })
+            .fold(0.0, f64::max)
+    });
+    Some(worst.sum::<f64>() / clusters.len() as f64)
+}
+
+/// Fraction of the samples in the most common class of their cluster - 1 when every
+/// cluster holds a single class, as it trivially does with a cluster per sample.
+pub fn purity<L: Into<usize> + Copy>(assignments: &[usize], labels: &[L]) -> f64 {
+    assert_eq!(assignments.len(), labels.len());
+    let mut counts: Vec<Vec<usize>> = vec![Vec::new(); cluster_sizes(assignments).len()];
+    for (&c, &l) in assignments.iter().zip(labels) {
+        let l = l.into();
+        if l >= counts[c].len() {
+            counts[c].resize(l + 1, 0);
+        }
+        counts[c][l] += 1;
+    }
+    let majority: usize = counts.iter().filter_map(|c| c.iter().max()).sum();
+    ratio(majority, assignments.len())
+}
+
+fn cluster_sizes(assignments: &[usize]) -> Vec<usize> {
+    let mut sizes = vec![0; assignments.iter().max().map_or(0, |&m| m + 1)];
+    for &c in assignments {
+        sizes[c] += 1;
+    }
+    sizes
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 { 0.0 } else { a as f64 / b as f64 }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_confusion_matrix() {
+        // 3 classes, truth 0: 5 samples, 1: 3, 2: 2
+        let labels: Vec<usize> = vec![0, 0, 0, 0, 0, 1, 1, 1, 2, 2];
+        let preds: Vec<usize> = vec![0, 0, 0, 0, 1, 1, 1, 0, 2, 1];
+        let m = ConfusionMatrix::from_predictions(&preds, &labels, 3);
+        assert_eq!((m.get(0, 1), m.get(1, 0), m.get(2, 1)), (1, 1, 1));
+        assert_eq!((m.correct(), m.total()), (7, 10));
+        assert!(close(m.accuracy(), 0.7));
+
+        assert!(close(m.precision(0), 0.8) && close(m.recall(0), 0.8));
+        assert!(close(m.precision(1), 0.5) && close(m.recall(1), 2.0 / 3.0));
+        assert!(close(m.precision(2), 1.0) && close(m.recall(2), 0.5));
+        assert!(close(m.f1(2), 2.0 / 3.0));
+
+        let macro_avg = m.macro_avg();
+        assert!(close(macro_avg.recall, (0.8 + 2.0 / 3.0 + 0.5) / 3.0));
+        assert!(close(m.balanced_accuracy(), macro_avg.recall));
+        // weighted recall is the accuracy
+        assert!(close(m.weighted_avg().recall, 0.7));
+
+        // p_e = (5*5 + 3*4 + 2*1) / 100
+        let pe = 0.39;
+        assert!(close(m.cohen_kappa(), (0.7 - pe) / (1.0 - pe)));
+
+        let csv = m.to_csv(&["a", "b", "c"]);
+        assert_eq!(csv.lines().next(), Some("true\\pred,a,b,c"));
+        assert_eq!(csv.lines().nth(1), Some("a,4,1,0"));
+        let report = m.report_csv::<&str>(&[]);
+        assert_eq!(report.lines().count(), 6);
+        assert!(report.lines().nth(1).unwrap().starts_with("0,0.8,0.8,"));
+        assert!(m.report(&["a", "b", "c"]).contains("kappa"));
+    }
+
+    #[test]
+    fn test_roc_auc() {
+        let positive = [false, false, true, true];
+        assert_eq!(roc_auc(&[0.1, 0.2, 0.3, 0.4], &positive), Some(1.0));
+        assert_eq!(roc_auc(&[0.4, 0.3, 0.2, 0.1], &positive), Some(0.0));
+        // of the four positive-negative pairs, one is tied and one inverted
+        assert_eq!(roc_auc(&[0.1, 0.3, 0.3, 0.2], &positive), Some(0.625));
+        assert_eq!(roc_auc(&[0.1, 0.2], &[true, true]), None);
+
+        let scores = vec![vec![0.9, 0.1], vec![0.2, 0.8], vec![0.6, 0.4]];
+        let labels: Vec<usize> = vec![0, 1, 0];
+        let aucs = roc_auc_ovr(&scores, &labels, 2);
+        assert_eq!(aucs, vec![Some(1.0), Some(1.0)]);
+        assert_eq!(macro_roc_auc(&aucs), Some(1.0));
+    }
+
+    #[test]
+    fn test_cluster_quality() {
+        use crate::types::binary::Binary;
+        use mersenne_twister_rs::MersenneTwister64;
+
+        let mut mt = MersenneTwister64::new(6);
+        let centers: Vec<Binary<4>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..60).map(|i| i % 3).collect();
+        let data: Vec<Binary<4>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(20, &mut mt))
+            .collect();
+        let shuffled: Vec<usize> = (0..60).map(|i| (i / 3) % 3).collect();
+
+        let good = silhouette(&data, &labels).unwrap();
+        assert!(good > 0.5 && good <= 1.0);
+        assert!(silhouette(&data, &shuffled).unwrap() < 0.1);
+        assert_eq!(silhouette(&data, &[0; 60]), None);
+        let alone: Vec<usize> = (0..60).map(|i| if i == 0 { 1 } else { 0 }).collect();
+        assert_eq!(silhouette_samples(&data, &alone)[0], 0.0);
+
+        let good = davies_bouldin(&data, &labels).unwrap();
+        assert!(good > 0.0 && good < davies_bouldin(&data, &shuffled).unwrap());
+        assert_eq!(davies_bouldin(&data, &[2; 60]), None);
+
+        assert!(close(purity(&labels, &labels), 1.0));
+        assert!(close(purity(&shuffled, &labels), 1.0 / 3.0));
+        assert!(close(purity(&[0; 60], &labels), 1.0 / 3.0));
+    }
+
+    #[test]
+    fn test_regression_metrics() {
+        let m = RegressionMetrics::new(&[1.0, 2.0, 5.0], &[1.0, 3.0, 3.0]);
+        assert!(close(m.mae, 1.0) && close(m.rmse, (5.0f64 / 3.0).sqrt()));
+        // squared errors sum to 5, squared deviations of the targets to 8/3
+        assert!(close(m.r2, 1.0 - 5.0 / (8.0 / 3.0)));
+        assert_eq!(RegressionMetrics::new(&[2.0], &[2.0]).r2, 0.0);
+    }
+
+    #[test]
+    fn test_multi_label() {
+        let labels: Vec<Vec<usize>> = vec![vec![0, 1], vec![2], vec![1], vec![]];
+        let preds: Vec<Vec<usize>> = vec![vec![1, 0], vec![1, 2], vec![], vec![]];
+        let m = MultiLabelMetrics::from_predictions(&preds, &labels, 3);
+        // one false positive and one false negative of 12 decisions
+        assert!(close(m.hamming_loss(), 2.0 / 12.0));
+        assert!(close(m.subset_accuracy(), 0.5));
+
+        let micro = m.micro_avg();
+        assert!(close(micro.precision, 0.75) && close(micro.recall, 0.75));
+        assert_eq!(micro.support, 4);
+        let per_label = m.label_metrics();
+        assert!(close(per_label[1].precision, 0.5) && close(per_label[1].recall, 0.5));
+        assert!(close(m.macro_avg().f1, (1.0 + 0.5 + 1.0) / 3.0));
+        assert!(m.report(&["a", "b", "c"]).contains("subset acc"));
+    }
+}
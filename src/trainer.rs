@@ -1,14 +1,53 @@
-use crate::{HyperVector, nearest};
+use crate::{Accumulator, HyperVector, nearest};
 use rayon::prelude::*;
 
+pub mod dbscan;
+pub mod driver;
+pub mod hierarchical;
 pub mod kmeans;
+pub mod kmedoids;
 pub mod lvq;
+pub mod model_selection;
+pub mod multi_label;
 pub mod multi_perceptron;
 pub mod pa;
 pub mod perceptron;
+pub mod persist;
+pub mod regression;
 
+/// Scores are similarities, 1 - distance: higher is closer. A model with several
+/// prototypes per class scores a class by its closest prototype.
 pub trait Classifier<T: HyperVector> {
-    fn predict(&self, h: &T) -> usize;
+    /// Per-class similarity of h, indexed by class
+    fn scores(&self, h: &T) -> Vec<f32>;
+
+    /// The class with the highest score
+    fn predict(&self, h: &T) -> usize {
+        argmax(&self.scores(h))
+    }
+
+    /// The k best classes with their scores, best first
+    fn top_k(&self, h: &T, k: usize) -> Vec<(usize, f32)> {
+        let mut ranked: Vec<(usize, f32)> = self.scores(h).into_iter().enumerate().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(k);
+        ranked
+    }
+
+    /// Score of the best class minus the score of the second best - small margins
+    /// are uncertain predictions. 0 for a model with a single class.
+    fn margin(&self, h: &T) -> f32 {
+        match self.top_k(h, 2)[..] {
+            [(_, first), (_, second)] => first - second,
+            _ => 0.0,
+        }
+    }
+
+    /// Softmax of scores / temperature. Scores of nearby hypervectors differ little,
+    /// so temperatures well below 1 are needed for confident probabilities.
+    fn probabilities(&self, h: &T, temperature: f32) -> Vec<f32> {
+        softmax(&self.scores(h), temperature)
+    }
 
     fn classify_all(&self, samples: &[T]) -> Vec<usize>
     where
@@ -49,7 +88,7 @@ pub trait Classifier<T: HyperVector> {
 }
 
 /// Result of a single training epoch.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EpochResult {
     pub epoch: usize,
     pub correct: usize,
@@ -73,18 +112,64 @@ pub struct PrototypeModel<T: HyperVector, const N: usize> {
 }
 
 impl<T: HyperVector, const N: usize> PrototypeModel<T, N> {
-    pub fn scores(&self, h: &T) -> [f32; N] {
-        std::array::from_fn(|i| self.prototypes[i].distance(&h))
+    /// Per-class distances - lower is closer, see ensemble_fusion
+    pub fn distances(&self, h: &T) -> [f32; N] {
+        std::array::from_fn(|i| self.prototypes[i].distance(h))
     }
 }
 
 impl<T: HyperVector, const N: usize> Classifier<T> for PrototypeModel<T, N> {
+    fn scores(&self, h: &T) -> Vec<f32> {
+        self.prototypes
+            .iter()
+            .map(|p| 1.0 - p.distance(h))
+            .collect()
+    }
+
+    fn predict(&self, h: &T) -> usize {
+        let (idx, _) = nearest(h, &self.prototypes);
+        idx
+    }
+}
+
+/// PrototypeModel with the number of classes known only at runtime
+pub struct DynPrototypeModel<T: HyperVector> {
+    pub prototypes: Vec<T>,
+}
+
+impl<T: HyperVector> DynPrototypeModel<T> {
+    pub fn n_classes(&self) -> usize {
+        self.prototypes.len()
+    }
+
+    /// Per-class distances - lower is closer, see ensemble_fusion
+    pub fn distances(&self, h: &T) -> Vec<f32> {
+        self.prototypes.iter().map(|p| p.distance(h)).collect()
+    }
+}
+
+impl<T: HyperVector> Classifier<T> for DynPrototypeModel<T> {
+    fn scores(&self, h: &T) -> Vec<f32> {
+        self.prototypes
+            .iter()
+            .map(|p| 1.0 - p.distance(h))
+            .collect()
+    }
+
     fn predict(&self, h: &T) -> usize {
         let (idx, _) = nearest(h, &self.prototypes);
         idx
     }
 }
 
+impl<T: HyperVector, const N: usize> From<PrototypeModel<T, N>> for DynPrototypeModel<T> {
+    fn from(model: PrototypeModel<T, N>) -> Self {
+        Self {
+            prototypes: model.prototypes.into(),
+        }
+    }
+}
+
 pub struct MultiPrototypeModel<T: HyperVector> {
     pub prototypes: Vec<T>,
     pub proto_labels: Vec<usize>, // class for each prototype, len = n_classes * proto_per_class
@@ -92,21 +177,166 @@ pub struct MultiPrototypeModel<T: HyperVector> {
     pub proto_per_class: usize,
 }
 
+impl<T: HyperVector> MultiPrototypeModel<T> {
+    /// Prototypes laid out class by class, proto_per_class of each - proto_labels
+    /// follow that layout
+    pub fn new(prototypes: Vec<T>, n_classes: usize, proto_per_class: usize) -> Self {
+        assert_eq!(prototypes.len(), n_classes * proto_per_class);
+        let proto_labels = (0..prototypes.len()).map(|i| i / proto_per_class).collect();
+        Self {
+            prototypes,
+            proto_labels,
+            n_classes,
+            proto_per_class,
+        }
+    }
+}
+
 impl<T: HyperVector> Classifier<T> for MultiPrototypeModel<T> {
+    fn scores(&self, h: &T) -> Vec<f32> {
+        let mut scores = vec![f32::NEG_INFINITY; self.n_classes];
+        for (p, &class) in self.prototypes.iter().zip(&self.proto_labels) {
+            scores[class] = scores[class].max(1.0 - p.distance(h));
+        }
+        scores
+    }
+
     fn predict(&self, h: &T) -> usize {
         let (idx, _) = nearest(h, &self.prototypes);
-        idx / self.proto_per_class
+        self.proto_labels[idx]
     }
 }
 
+// Updates per parallel partial sum. Partial sums are merged in chunk order, so the
+// result does not depend on the number of threads.
+const CHUNK: usize = 256;
+
+/// Number of classes of the labels - the largest class index + 1
+pub fn n_classes<L: Into<usize> + Copy>(labels: &[L]) -> usize {
+    labels.iter().map(|&l| l.into() + 1).max().unwrap_or(0)
+}
+
+/// Applies `updates` - (sample index, accumulator index, weight) - to `accumulators`,
+/// building per-chunk partial sums in parallel.
+pub fn par_accumulate<T>(
+    accumulators: &mut [T::Accumulator],
+    samples: &[T],
+    updates: &[(usize, usize, f64)],
+) where
+    T: HyperVector + Sync,
+{
+    let n = accumulators.len();
+    let partial: Vec<Vec<T::Accumulator>> = updates
+        .par_chunks(CHUNK)
+        .map(|chunk| {
+            let mut accs: Vec<T::Accumulator> = (0..n).map(|_| T::Accumulator::new()).collect();
+            for &(idx, acc, weight) in chunk {
+                accs[acc].add(&samples[idx], weight);
+            }
+            accs
+        })
+        .collect();
+    for accs in partial {
+        for (acc, part) in accumulators.iter_mut().zip(&accs) {
+            acc.merge(part);
+        }
+    }
+}
+
+// Panics with the offending label rather than an index out of bounds in a worker
+pub(crate) fn check_labels<L: Into<usize> + Copy>(labels: &[L], n_classes: usize) {
+    if let Some(&label) = labels.iter().find(|&&l| l.into() >= n_classes) {
+        panic!(
+            "label {} out of range for {n_classes} classes",
+            label.into()
+        );
+    }
+}
+
+/// One accumulator per class with the samples of that class added, weight 1 -
+/// except the `exclude`d sample, held out for leave-one-out.
+/// Panics on a label >= n_classes.
+pub fn class_accumulators<T, L>(
+    samples: &[T],
+    labels: &[L],
+    exclude: Option<usize>,
+    n_classes: usize,
+) -> Vec<T::Accumulator>
+where
+    T: HyperVector + Sync,
+    L: Into<usize> + Copy,
+{
+    check_labels(labels, n_classes);
+    let mut accumulators: Vec<T::Accumulator> =
+        (0..n_classes).map(|_| T::Accumulator::new()).collect();
+    let updates: Vec<(usize, usize, f64)> = labels
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| Some(i) != exclude)
+        .map(|(i, &label)| (i, label.into(), 1.0))
+        .collect();
+    par_accumulate(&mut accumulators, samples, &updates);
+    accumulators
+}
+
 pub trait Trainer<T: HyperVector> {
     type Model: Classifier<T>;
     fn step(&mut self, epoch: usize) -> EpochResult;
 
+    /// Scores the model as trained so far on the training samples. The result of
+    /// step may be of the model before the epoch, or counted during it.
+    fn score(&self, epoch: usize) -> EpochResult;
+
+    /// The model as trained so far - a copy of the current prototypes.
+    fn model(&self) -> Self::Model;
+
     /// Consume the trainer and return the final trained model.
     fn into_model(self) -> Self::Model;
 }
 
+/// Learning rate per epoch (1-based) of the perceptron, LVQ and regression trainers.
+/// The PA trainers compute their step size from the loss instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    Constant(f64),
+    /// lr / sqrt(epoch) - the default, with lr 1
+    InvSqrt(f64),
+    /// lr / epoch
+    InvTime(f64),
+    /// lr * factor^((epoch - 1) / every) - factor applied every `every` epochs
+    Step {
+        lr: f64,
+        factor: f64,
+        every: usize,
+    },
+    /// lr * decay^(epoch - 1)
+    Exponential {
+        lr: f64,
+        decay: f64,
+    },
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule::InvSqrt(1.0)
+    }
+}
+
+impl Schedule {
+    pub fn rate(&self, epoch: usize) -> f64 {
+        let epoch = epoch.max(1);
+        match *self {
+            Schedule::Constant(lr) => lr,
+            Schedule::InvSqrt(lr) => lr / (epoch as f64).sqrt(),
+            Schedule::InvTime(lr) => lr / epoch as f64,
+            Schedule::Step { lr, factor, every } => {
+                lr * factor.powi(((epoch - 1) / every.max(1)) as i32)
+            }
+            Schedule::Exponential { lr, decay } => lr * decay.powi((epoch - 1) as i32),
+        }
+    }
+}
+
 pub fn ensemble_vote<L>(predictions: &[Vec<usize>], n_classes: usize) -> Vec<usize> {
     let n_samples = predictions[0].len();
     (0..n_samples)
@@ -145,21 +375,26 @@ where
 
 /// Sum-rule fusion for score-based ensembles.
 ///
-/// `scores` holds one `Vec<[f32; N]>` per model, each indexed by sample.
-/// Each entry is that model's per-class distance for the sample (lower = closer).
+/// `scores` holds one `Vec` of per-class distances per model, each indexed by
+/// sample - `[f32; N]` from PrototypeModel::distances or `Vec<f32>` from
+/// DynPrototypeModel::distances (lower = closer).
 /// The fused prediction is the class with the smallest *summed* distance
 /// across all models. Valid to sum directly (no per-model normalization)
 /// as long as every model shares the same encoder/dimensionality, so their
 /// distance scales are already comparable.
-pub fn ensemble_fusion<const N: usize>(scores: &[Vec<[f32; N]>]) -> Vec<usize> {
+pub fn ensemble_fusion<S>(scores: &[Vec<S>]) -> Vec<usize>
+where
+    S: AsRef<[f32]> + Sync,
+{
     let n_samples = scores[0].len();
+    let n_classes = scores[0][0].as_ref().len();
     (0..n_samples)
         .into_par_iter()
         .map(|i| {
-            let mut summed = [0f32; N];
+            let mut summed = vec![0f32; n_classes];
             for model_scores in scores {
-                for c in 0..N {
-                    summed[c] += model_scores[i][c];
+                for (sum, s) in summed.iter_mut().zip(model_scores[i].as_ref()) {
+                    *sum += s;
                 }
             }
             argmin(&summed)
@@ -173,24 +408,46 @@ pub fn ensemble_fusion<const N: usize>(scores: &[Vec<[f32; N]>]) -> Vec<usize> {
         .collect()
 }
 
-pub fn argmin<const N: usize>(scores: &[f32; N]) -> usize {
+/// Index of the highest score - the first one on ties
+pub fn argmax(scores: &[f32]) -> usize {
+    let mut max_idx = 0;
+    for (i, &s) in scores.iter().enumerate().skip(1) {
+        if s > scores[max_idx] {
+            max_idx = i;
+        }
+    }
+    max_idx
+}
+
+/// Softmax of scores / temperature, shifted by the maximum for stability
+pub fn softmax(scores: &[f32], temperature: f32) -> Vec<f32> {
+    assert!(temperature > 0.0, "temperature must be positive");
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = scores
+        .iter()
+        .map(|s| ((s - max) / temperature).exp())
+        .collect();
+    let sum: f32 = exps.iter().sum();
+    exps.into_iter().map(|e| e / sum).collect()
+}
+
+/// Index of the lowest score - the first one on ties
+pub fn argmin(scores: &[f32]) -> usize {
     let mut min_idx = 0;
     let mut min_val = scores[0];
-    for i in 1..N {
-        if scores[i] < min_val {
-            min_val = scores[i];
+    for (i, &s) in scores.iter().enumerate().skip(1) {
+        if s < min_val {
+            min_val = s;
             min_idx = i;
         }
     }
     min_idx
 }
 
-pub fn ensemble_fusion_accuracy<L, const N: usize>(
-    scores: &[Vec<[f32; N]>],
-    labels: &[L],
-) -> (usize, usize, f64)
+pub fn ensemble_fusion_accuracy<L, S>(scores: &[Vec<S>], labels: &[L]) -> (usize, usize, f64)
 where
     L: Into<usize> + Copy,
+    S: AsRef<[f32]> + Sync,
 {
     let preds = ensemble_fusion(scores);
     let correct = preds
@@ -201,3 +458,240 @@ where
     let total = labels.len();
     (correct, total - correct, correct as f64 / total as f64)
 }
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod trainer_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_scores() {
+        let mut mt = MersenneTwister64::new(42);
+        let model = PrototypeModel::<Binary<16>, 3> {
+            prototypes: std::array::from_fn(|_| Binary::random(&mut mt)),
+        };
+        let query = model.prototypes[1].flip(100, &mut mt);
+
+        let scores = model.scores(&query);
+        assert_eq!(scores.len(), 3);
+        assert_eq!(model.predict(&query), 1);
+        assert_eq!(argmax(&scores), 1);
+        let top = model.top_k(&query, 2);
+        assert_eq!(top[0], (1, scores[1]));
+        assert!(top[1].1 <= top[0].1);
+        assert!((model.margin(&query) - (top[0].1 - top[1].1)).abs() < 1e-6);
+        assert_eq!(model.top_k(&query, 10).len(), 3);
+
+        let p = model.probabilities(&query, 0.05);
+        assert!((p.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert_eq!(argmax(&p), 1);
+        // a lower temperature is more confident
+        assert!(model.probabilities(&query, 0.01)[1] > p[1]);
+    }
+
+    #[test]
+    fn test_multi_prototype_scores() {
+        let mut mt = MersenneTwister64::new(13);
+        let centers: Vec<Binary<8>> = (0..6).map(|_| Binary::random(&mut mt)).collect();
+        // 3 classes of 2 clusters each
+        let labels: Vec<usize> = (0..180).map(|i| i % 3).collect();
+        let samples: Vec<Binary<8>> = (0..180)
+            .map(|i| centers[2 * (i % 3) + (i / 3) % 2].flip(60, &mut mt))
+            .collect();
+
+        let check = |model: MultiPrototypeModel<Binary<8>>| {
+            assert_eq!(model.proto_labels, vec![0, 0, 1, 1, 2, 2]);
+            for h in &samples {
+                // a class scores by its closest prototype
+                let scores = model.scores(h);
+                let closest = |c: usize| {
+                    (2 * c..2 * c + 2)
+                        .map(|i| 1.0 - model.prototypes[i].distance(h))
+                        .fold(f32::NEG_INFINITY, f32::max)
+                };
+                assert_eq!(scores, (0..3).map(closest).collect::<Vec<_>>());
+                assert_eq!(scores.len(), 3);
+                assert_eq!(argmax(&scores), model.predict(h));
+                assert_eq!(model.top_k(h, 3)[0].0, model.predict(h));
+                assert!(model.margin(h) >= 0.0);
+                assert!((model.probabilities(h, 0.1).iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            }
+            assert_eq!(model.accuracy(&samples, &labels).0, samples.len());
+        };
+        let trainer = multi_perceptron::PerceptronMultiTrainer::new(
+            &samples,
+            &labels,
+            None,
+            3,
+            2,
+            MersenneTwister64::new(1),
+        );
+        let model = trainer.fit(10).0;
+
+        // predict follows proto_labels, as scores do
+        let mut relabelled = MultiPrototypeModel::new(model.prototypes.clone(), 3, 2);
+        relabelled.proto_labels = vec![2, 2, 0, 0, 1, 1];
+        for h in &samples {
+            assert_eq!(relabelled.predict(h), (model.predict(h) + 2) % 3);
+            assert_eq!(relabelled.predict(h), argmax(&relabelled.scores(h)));
+        }
+        check(model);
+        let trainer = lvq::LvqTrainer::new(
+            &samples,
+            &labels,
+            None,
+            3,
+            2,
+            MersenneTwister64::new(1),
+            0.25,
+        );
+        check(trainer.fit(10).0);
+    }
+
+    #[test]
+    fn test_dyn_prototypes() {
+        let mut mt = MersenneTwister64::new(7);
+        let centers: Vec<Binary<16>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..60).map(|i| i % 3).collect();
+        let samples: Vec<Binary<16>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(200, &mut mt))
+            .collect();
+        assert_eq!(n_classes(&labels), 3);
+
+        let trainer = perceptron::DynPerceptronTrainer::new(
+            &samples,
+            &labels,
+            None,
+            n_classes(&labels),
+            MersenneTwister64::new(1),
+        );
+        let (model, _) = trainer.fit(10);
+        assert_eq!(model.n_classes(), 3);
+        assert_eq!(model.accuracy(&samples, &labels).0, samples.len());
+
+        // the same as the const generic trainer
+        let trainer = perceptron::PerceptronTrainer::<_, _, _, 3>::new(
+            &samples,
+            &labels,
+            None,
+            MersenneTwister64::new(1),
+        );
+        let (fixed, _) = trainer.fit(10);
+        assert_eq!(fixed.prototypes[..], model.prototypes[..]);
+
+        // fusion of runtime sized and fixed size distances
+        let dyn_scores: Vec<Vec<f32>> = samples.iter().map(|h| model.distances(h)).collect();
+        let fixed_scores: Vec<[f32; 3]> = samples.iter().map(|h| fixed.distances(h)).collect();
+        let fused = ensemble_fusion(&[dyn_scores.clone(), dyn_scores]);
+        assert_eq!(fused, ensemble_fusion(&[fixed_scores]));
+        assert_eq!(fused, labels);
+    }
+
+    #[test]
+    #[should_panic(expected = "label 3 out of range for 3 classes")]
+    fn test_label_out_of_range() {
+        let mut mt = MersenneTwister64::new(5);
+        let samples: Vec<Binary<8>> = (0..4).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = vec![0, 1, 2, 3];
+        perceptron::DynPerceptronTrainer::new(&samples, &labels, None, 3, mt);
+    }
+
+    #[test]
+    #[should_panic(expected = "label 3 out of range for 3 classes")]
+    fn test_multi_label_out_of_range() {
+        let mut mt = MersenneTwister64::new(5);
+        let samples: Vec<Binary<8>> = (0..8).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = vec![0, 1, 2, 3, 0, 1, 2, 3];
+        lvq::LvqTrainer::new(&samples, &labels, None, 3, 2, mt, 0.25);
+    }
+
+    #[test]
+    fn test_exclude() {
+        let mut mt = MersenneTwister64::new(5);
+        let centers: Vec<Binary<8>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..60).map(|i| i % 3).collect();
+        let samples: Vec<Binary<8>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(150, &mut mt))
+            .collect();
+        // the initial prototypes are the same as without the excluded sample
+        let held_out = 7;
+        let rest_samples: Vec<Binary<8>> = (0..60)
+            .filter(|&i| i != held_out)
+            .map(|i| samples[i].clone())
+            .collect();
+        let rest_labels: Vec<usize> = (0..60)
+            .filter(|&i| i != held_out)
+            .map(|i| labels[i])
+            .collect();
+
+        let single = |samples, labels, exclude| {
+            perceptron::DynPerceptronTrainer::new(
+                samples,
+                labels,
+                exclude,
+                3,
+                MersenneTwister64::new(1),
+            )
+            .model()
+            .prototypes
+        };
+        assert_eq!(
+            single(&samples, &labels, Some(held_out)),
+            single(&rest_samples, &rest_labels, None)
+        );
+        let pa = |samples, labels, exclude| {
+            pa::DynPaTrainer::new(
+                samples,
+                labels,
+                exclude,
+                3,
+                pa::PaVariant::Pa,
+                MersenneTwister64::new(1),
+            )
+            .model()
+            .prototypes
+        };
+        assert_eq!(
+            pa(&samples, &labels, Some(held_out)),
+            pa(&rest_samples, &rest_labels, None)
+        );
+        let multi = |samples, labels, exclude| {
+            multi_perceptron::PerceptronMultiTrainer::new(
+                samples,
+                labels,
+                exclude,
+                3,
+                2,
+                MersenneTwister64::new(1),
+            )
+            .model()
+            .prototypes
+        };
+        assert_eq!(
+            multi(&samples, &labels, Some(held_out)),
+            multi(&rest_samples, &rest_labels, None)
+        );
+        let lvq = |samples, labels, exclude| {
+            lvq::LvqTrainer::new(
+                samples,
+                labels,
+                exclude,
+                3,
+                2,
+                MersenneTwister64::new(1),
+                0.25,
+            )
+            .model()
+            .prototypes
+        };
+        assert_eq!(
+            lvq(&samples, &labels, Some(held_out)),
+            lvq(&rest_samples, &rest_labels, None)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::HyperVector;
+use rayon::prelude::*;
+
+// ── DBSCAN ────────────────────────────────────────────────────────────────────
+//
+// Density-based clustering: a sample with at least min_points samples - itself
+// included - within eps is a core sample. Clusters are the core samples connected
+// by chains of core samples within eps, plus the samples within eps of them.
+// Samples of no cluster are noise. The number of clusters is not given but found,
+// and clusters need not be round.
+
+/// DBSCAN with neighbourhood radius eps, e.g. a normalized Hamming distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dbscan {
+    pub eps: f32,
+    pub min_points: usize,
+}
+
+impl Dbscan {
+    pub fn new(eps: f32, min_points: usize) -> Self {
+        Self { eps, min_points }
+    }
+
+    /// Cluster of every sample - None for noise. Clusters are numbered in order of
+    /// their first core sample, and a sample within eps of several clusters goes
+    /// to the first. The neighbourhoods are found in parallel, in O(n²).
+    pub fn fit<H: HyperVector + Sync>(&self, data: &[H]) -> Vec<Option<usize>> {
+        let neighbours: Vec<Vec<usize>> = data
+            .par_iter()
+            .map(|a| {
+                (0..data.len())
+                    .filter(|&j| a.distance(&data[j]) <= self.eps)
+                    .collect()
+            })
+            .collect();
+        let core: Vec<bool> = neighbours
+            .iter()
+            .map(|n| n.len() >= self.min_points)
+            .collect();
+
+        let mut labels = vec![None; data.len()];
+        let mut n_clusters = 0;
+        for i in 0..data.len() {
+            if !core[i] || labels[i].is_some() {
+                continue;
+            }
+            labels[i] = Some(n_clusters);
+            let mut queue = vec![i];
+            while let Some(j) = queue.pop() {
+                for &k in &neighbours[j] {
+                    if labels[k].is_none() {
+                        labels[k] = Some(n_clusters);
+                        // only core samples extend the cluster
+                        if core[k] {
+                            queue.push(k);
+                        }
+                    }
+                }
+            }
+            n_clusters += 1;
+        }
+        labels
+    }
+}
+
+/// Distance of every sample to its k-th nearest other sample, in decreasing order.
+/// The knee of this curve, with k = min_points - 1, is a common choice of eps.
+pub fn k_distances<H: HyperVector + Sync>(data: &[H], k: usize) -> Vec<f32> {
+    assert!(k > 0 && k < data.len(), "k must be > 0 and < data length");
+    let mut dists: Vec<f32> = data
+        .par_iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let mut d: Vec<f32> = (0..data.len())
+                .filter(|&j| j != i)
+                .map(|j| a.distance(&data[j]))
+                .collect();
+            *d.select_nth_unstable_by(k - 1, f32::total_cmp).1
+        })
+        .collect();
+    dists.sort_by(|a, b| b.total_cmp(a));
+    dists
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod dbscan_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_dbscan() {
+        let mut mt = MersenneTwister64::new(4);
+        let centers: Vec<Binary<4>> = (0..2).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..40).map(|i| i % 2).collect();
+        let mut data: Vec<Binary<4>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(10, &mut mt))
+            .collect();
+        // 3 random outliers, ~0.5 from everything
+        data.extend((0..3).map(|_| Binary::<4>::random(&mut mt)));
+
+        // samples of a center are within 2 * 10 / 256 of each other
+        let clusters = Dbscan::new(0.1, 4).fit(&data);
+        assert_eq!(&clusters[..4], &[Some(0), Some(1), Some(0), Some(1)]);
+        for i in 0..40 {
+            assert_eq!(clusters[i], Some(labels[i]));
+        }
+        assert_eq!(&clusters[40..], &[None, None, None]);
+
+        // too dense a requirement - everything is noise
+        assert!(Dbscan::new(0.1, 30).fit(&data).iter().all(|c| c.is_none()));
+
+        let kd = k_distances(&data, 3);
+        assert_eq!(kd.len(), 43);
+        assert!(kd[..3].iter().all(|&d| d > 0.3) && kd[3..].iter().all(|&d| d < 0.1));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::HyperVector;
+use crate::trainer::{Classifier, EpochResult, Trainer};
+use std::io::Write;
+
+// ── Training driver ───────────────────────────────────────────────────────────
+//
+// Runs Trainer::step epoch by epoch, scoring every epoch on a held-out
+// validation set - or on the training set when there is none, with Trainer::score,
+// as the result of step need not be of the model after it. The model of the
+// best epoch is kept, and training stops
+//   - after max_epochs,
+//   - when the score has not improved for `patience` epochs,
+//   - when an epoch has no training errors (unless disabled).
+
+/// Training and validation results of an epoch, passed to the callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochLog {
+    /// The result of Trainer::step - or, without a validation set, the score of
+    /// the model after the epoch on the training set
+    pub train: EpochResult,
+    pub validation: Option<EpochResult>,
+    /// The best epoch so far, including this one
+    pub best_epoch: usize,
+}
+
+/// The model of the best epoch and the log of all epochs run
+pub struct Fitted<M> {
+    pub model: M,
+    pub best_epoch: usize,
+    pub log: Vec<EpochLog>,
+}
+
+impl<M> Fitted<M> {
+    /// The training results of all epochs run
+    pub fn history(&self) -> Vec<EpochResult> {
+        self.log.iter().map(|l| l.train).collect()
+    }
+}
+
+type Callback<'a> = Box<dyn FnMut(&EpochLog) + 'a>;
+
+pub struct Driver<'a, T> {
+    max_epochs: usize,
+    patience: Option<usize>,
+    validation: Option<(&'a [T], Vec<usize>)>,
+    stop_at_zero_errors: bool,
+    progress: bool,
+    callbacks: Vec<Callback<'a>>,
+}
+
+impl<'a, T> Driver<'a, T>
+where
+    T: HyperVector + Send + Sync,
+{
+    pub fn new(max_epochs: usize) -> Self {
+        Self {
+            max_epochs,
+            patience: None,
+            validation: None,
+            stop_at_zero_errors: true,
+            progress: false,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Score the epochs on these samples instead of the training set
+    pub fn with_validation<L>(mut self, samples: &'a [T], labels: &[L]) -> Self
+    where
+        L: Into<usize> + Copy,
+    {
+        assert!(!samples.is_empty() && samples.len() == labels.len());
+        let labels = labels.iter().map(|&l| l.into()).collect();
+        self.validation = Some((samples, labels));
+        self
+    }
+
+    /// Stop when the score has not improved for `patience` epochs
+    pub fn with_patience(mut self, patience: usize) -> Self {
+        self.patience = Some(patience);
+        self
+    }
+
+    /// Keep training after an epoch without training errors - the default is to stop
+    pub fn with_stop_at_zero_errors(mut self, stop: bool) -> Self {
+        self.stop_at_zero_errors = stop;
+        self
+    }
+
+    /// Called after every epoch
+    pub fn with_callback(mut self, callback: impl FnMut(&EpochLog) + 'a) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Prints the accuracies of every epoch on one line, see print_progress
+    pub fn with_progress(mut self) -> Self {
+        self.progress = true;
+        self.with_callback(print_progress)
+    }
+
+    pub fn run<Tr>(mut self, mut trainer: Tr) -> Fitted<Tr::Model>
+    where
+        Tr: Trainer<T>,
+        Tr::Model: Sync,
+    {
+        let mut log = Vec::with_capacity(self.max_epochs);
+        let mut best_epoch = 0;
+        let mut best_correct = 0;
+        // snapshot of the best epoch, taken when training goes on after it
+        let mut best: Option<Tr::Model> = None;
+
+        for epoch in 1..=self.max_epochs {
+            let mut train = trainer.step(epoch);
+            if self.validation.is_none() {
+                train = trainer.score(epoch);
+            }
+            let validation = self.validation.as_ref().map(|(samples, labels)| {
+                let (correct, errors, _) = trainer.model().accuracy(samples, labels);
+                EpochResult {
+                    epoch,
+                    correct,
+                    errors,
+                }
+            });
+
+            // ties go to the earlier epoch
+            let correct = validation.unwrap_or(train).correct;
+            if best_epoch == 0 || correct > best_correct {
+                best_epoch = epoch;
+                best_correct = correct;
+            }
+
+            let entry = EpochLog {
+                train,
+                validation,
+                best_epoch,
+            };
+            log.push(entry);
+            for callback in &mut self.callbacks {
+                callback(&entry);
+            }
+
+            let out_of_patience = self.patience.is_some_and(|p| epoch - best_epoch >= p);
+            let converged = self.stop_at_zero_errors && train.errors == 0;
+            if out_of_patience || converged {
+                break;
+            }
+            if best_epoch == epoch && epoch < self.max_epochs {
+                best = Some(trainer.model());
+            }
+        }
+        if self.progress {
+            println!();
+        }
+
+        let model = if best_epoch == log.len() {
+            trainer.into_model()
+        } else {
+            best.expect("snapshot of the best epoch")
+        };
+        Fitted {
+            model,
+            best_epoch,
+            log,
+        }
+    }
+}
+
+/// Prints the training and validation accuracy of the epoch, overwriting the line
+pub fn print_progress(log: &EpochLog) {
+    let r = log.train;
+    print!(
+        "Epoch {}: Training Accuracy {}/{}={:.2}%",
+        r.epoch,
+        r.correct,
+        r.total(),
+        r.accuracy() * 100.0
+    );
+    if let Some(v) = log.validation {
+        print!(
+            "  Validation {}/{}={:.2}% (best epoch {})",
+            v.correct,
+            v.total(),
+            v.accuracy() * 100.0,
+            log.best_epoch
+        );
+    }
+    print!("\r");
+    std::io::stdout().flush().unwrap();
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod driver_tests {
+    use super::*;
+    use crate::trainer::Schedule;
+    use crate::trainer::perceptron::DynPerceptronTrainer;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+    use rand::RngExt;
+
+    #[test]
+    fn test_schedule() {
+        assert_eq!(Schedule::default().rate(4), 0.5);
+        assert_eq!(Schedule::Constant(0.3).rate(10), 0.3);
+        assert_eq!(Schedule::InvTime(1.0).rate(4), 0.25);
+        let step = Schedule::Step {
+            lr: 1.0,
+            factor: 0.5,
+            every: 2,
+        };
+        assert_eq!([1, 2, 3, 5].map(|e| step.rate(e)), [1.0, 1.0, 0.5, 0.25]);
+        let exp = Schedule::Exponential {
+            lr: 2.0,
+            decay: 0.5,
+        };
+        assert_eq!(exp.rate(3), 0.5);
+    }
+
+    #[test]
+    fn test_driver() {
+        let mut mt = MersenneTwister64::new(3);
+        let centers: Vec<Binary<4>> = (0..4).map(|_| Binary::random(&mut mt)).collect();
+        // noisy samples and 10% wrong labels - training does not reach zero errors
+        let mut labels: Vec<usize> = (0..400).map(|i| i % 4).collect();
+        let samples: Vec<Binary<4>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(100, &mut mt))
+            .collect();
+        for l in labels.iter_mut().step_by(10) {
+            *l = mt.random_range(0..4);
+        }
+        let (train, valid) = samples.split_at(300);
+        let (train_labels, valid_labels) = labels.split_at(300);
+
+        let trainer = DynPerceptronTrainer::new(train, train_labels, None, 4, mt.clone())
+            .with_schedule(Schedule::Constant(1.0));
+        let mut calls = 0;
+        let fitted = Driver::new(50)
+            .with_validation(valid, valid_labels)
+            .with_patience(3)
+            .with_callback(|_| calls += 1)
+            .run(trainer);
+
+        let n = fitted.log.len();
+        assert_eq!(calls, n);
+        assert!(n <= 50 && fitted.best_epoch <= n);
+        assert!(n == 50 || n == fitted.best_epoch + 3);
+        let best = fitted.log[fitted.best_epoch - 1].validation.unwrap();
+        assert!(
+            fitted
+                .log
+                .iter()
+                .all(|l| l.validation.unwrap().correct <= best.correct)
+        );
+        // the model of the best epoch is returned
+        assert_eq!(fitted.model.accuracy(valid, valid_labels).0, best.correct);
+        assert_eq!(fitted.history().len(), n);
+
+        // without validation the training scores pick the model
+        let trainer = DynPerceptronTrainer::new(train, train_labels, None, 4, mt)
+            .with_schedule(Schedule::Constant(1.0));
+        let fitted = Driver::new(20).run(trainer);
+        let best = fitted.log[fitted.best_epoch - 1].train;
+        assert!(fitted.log.iter().all(|l| l.train.correct <= best.correct));
+        assert_eq!(fitted.model.accuracy(train, train_labels).0, best.correct);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{HyperVector, distance_matrix};
+use std::fmt;
+
+// ── Agglomerative clustering ──────────────────────────────────────────────────
+//
+// Starts with every sample in a cluster of its own and repeatedly merges the two
+// nearest clusters. The distance between a merged cluster and the others follows
+// from the distances of its two parts (Lance-Williams), so only the initial
+// pairwise distances are computed - n² floats, for up to a few thousand samples.
+//
+// Clusters are numbered as in scipy's linkage matrix: cluster i < n is sample i,
+// cluster n + m is the result of merge m.
+
+/// Distance between two clusters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Distance of the nearest pair of members
+    Single,
+    /// Distance of the farthest pair of members
+    Complete,
+    /// Mean distance over all pairs of members
+    Average,
+}
+
+/// A merge of clusters a < b at a distance into a cluster of `size` samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Merge {
+    pub a: usize,
+    pub b: usize,
+    pub distance: f32,
+    pub size: usize,
+}
+
+/// The n - 1 merges of n samples, by increasing distance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dendrogram {
+    pub n: usize,
+    pub merges: Vec<Merge>,
+}
+
+/// Agglomerative clustering of the samples. Ties are merged in sample order.
+pub fn agglomerative<H: HyperVector + Sync>(data: &[H], linkage: Linkage) -> Dendrogram {
+    let n = data.len();
+    let mut dist = distance_matrix(data);
+    // a slot holds a cluster until it is merged into a lower slot
+    let mut active = vec![true; n];
+    let mut id: Vec<usize> = (0..n).collect();
+    let mut size = vec![1; n];
+
+    // nearest active slot of every active slot, and its distance
+    let nearest_of = |dist: &[Vec<f32>], active: &[bool], i: usize| -> (usize, f32) {
+        (0..n)
+            .filter(|&j| j != i && active[j])
+            .map(|j| (j, dist[i][j]))
+            .fold(
+                (usize::MAX, f32::INFINITY),
+                |a, b| if b.1 < a.1 { b } else { a },
+            )
+    };
+    let mut nn: Vec<(usize, f32)> = (0..n).map(|i| nearest_of(&dist, &active, i)).collect();
+
+    let mut merges = Vec::with_capacity(n.saturating_sub(1));
+    for m in 0..n.saturating_sub(1) {
+        let i = (0..n).filter(|&i| active[i]).fold(usize::MAX, |a, i| {
+            if a == usize::MAX || nn[i].1 < nn[a].1 {
+                i
+            } else {
+                a
+            }
+        });
+        let (a, b) = (i.min(nn[i].0), i.max(nn[i].0));
+
+        merges.push(Merge {
+            a: id[a].min(id[b]),
+            b: id[a].max(id[b]),
+            distance: dist[a][b],
+            size: size[a] + size[b],
+        });
+        active[b] = false;
+        for k in (0..n).filter(|&k| active[k] && k != a) {
+            let d = match linkage {
+                Linkage::Single => dist[a][k].min(dist[b][k]),
+                Linkage::Complete => dist[a][k].max(dist[b][k]),
+                Linkage::Average => {
+                    (size[a] as f32 * dist[a][k] + size[b] as f32 * dist[b][k])
+                        / (size[a] + size[b]) as f32
+                }
+            };
+            dist[a][k] = d;
+            dist[k][a] = d;
+        }
+        id[a] = n + m;
+        size[a] += size[b];
+
+        for k in (0..n).filter(|&k| active[k]) {
+            if k == a || nn[k].0 == a || nn[k].0 == b {
+                nn[k] = nearest_of(&dist, &active, k);
+            } else if dist[k][a] < nn[k].1 || (dist[k][a] == nn[k].1 && a < nn[k].0) {
+                nn[k] = (a, dist[k][a]);
+            }
+        }
+    }
+    Dendrogram { n, merges }
+}
+
+impl Dendrogram {
+    /// Flat clustering into k clusters - the first n - k merges. Clusters are
+    /// numbered in order of their first sample.
+    pub fn cut(&self, k: usize) -> Vec<usize> {
+        assert!(
+            k > 0 && k <= self.n,
+            "k must be > 0 and <= number of samples"
+        );
+        self.labels(self.n - k)
+    }
+
+    /// Flat clustering by the merges at no more than this distance
+    pub fn cut_at(&self, distance: f32) -> Vec<usize> {
+        let merged = self
+            .merges
+            .iter()
+            .filter(|m| m.distance <= distance)
+            .count();
+        self.labels(merged)
+    }
+
+    fn labels(&self, n_merges: usize) -> Vec<usize> {
+        let mut parent: Vec<usize> = (0..self.n + n_merges).collect();
+        for (m, merge) in self.merges[..n_merges].iter().enumerate() {
+            parent[merge.a] = self.n + m;
+            parent[merge.b] = self.n + m;
+        }
+        let mut number = vec![usize::MAX; parent.len()];
+        let mut next = 0;
+        (0..self.n)
+            .map(|i| {
+                let mut root = i;
+                while parent[root] != root {
+                    root = parent[root];
+                }
+                if number[root] == usize::MAX {
+                    number[root] = next;
+                    next += 1;
+                }
+                number[root]
+            })
+            .collect()
+    }
+
+    /// The tree in Newick format, e.g. for plotting. Leaves are named by `names` -
+    /// the sample index where there are too few - and branch lengths are the
+    /// differences of the merge distances.
+    pub fn to_newick<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let leaf = |i: usize| {
+            names
+                .get(i)
+                .map_or_else(|| i.to_string(), |s| s.as_ref().to_string())
+        };
+        // subtree and height of every cluster, built bottom up
+        let mut trees: Vec<(String, f32)> = (0..self.n).map(|i| (leaf(i), 0.0)).collect();
+        for merge in &self.merges {
+            let (a, ha) = std::mem::take(&mut trees[merge.a]);
+            let (b, hb) = std::mem::take(&mut trees[merge.b]);
+            let h = merge.distance;
+            trees.push((format!("({a}:{},{b}:{})", h - ha, h - hb), h));
+        }
+        match trees.pop() {
+            Some((tree, _)) if self.n > 1 => format!("{tree};"),
+            _ => format!("{};", (0..self.n).map(leaf).collect::<String>()),
+        }
+    }
+}
+
+/// The merges as rows of scipy's linkage matrix: a, b, distance and size
+impl fmt::Display for Dendrogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>6} {:>6} {:>10} {:>6}", "a", "b", "distance", "size")?;
+        for m in &self.merges {
+            writeln!(f, "{:6} {:6} {:10.6} {:6}", m.a, m.b, m.distance, m.size)?;
+        }
+        Ok(())
+    }
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod hierarchical_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_agglomerative() {
+        let mut mt = MersenneTwister64::new(8);
+        let centers: Vec<Binary<4>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..60).map(|i| i % 3).collect();
+        let data: Vec<Binary<4>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(30, &mut mt))
+            .collect();
+
+        for linkage in [Linkage::Single, Linkage::Complete, Linkage::Average] {
+            let tree = agglomerative(&data, linkage);
+            assert_eq!(tree.merges.len(), 59);
+            assert_eq!(tree.merges[58].size, 60);
+            assert!(
+                tree.merges
+                    .windows(2)
+                    .all(|w| w[0].distance <= w[1].distance)
+            );
+            // the 3 clusters are the generating centers, numbered by first sample
+            assert_eq!(tree.cut(3), labels);
+            assert_eq!(tree.cut_at(tree.merges[56].distance), labels);
+            assert_eq!(tree.cut(1), vec![0; 60]);
+            assert_eq!(tree.cut(60), (0..60).collect::<Vec<_>>());
+        }
+
+        // the first merge is the nearest pair, the last complete one the farthest
+        let dist = distance_matrix(&data);
+        let pairs = dist
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row[i + 1..].iter());
+        let (min, max) = pairs.fold((f32::MAX, 0.0f32), |(lo, hi), &d| (lo.min(d), hi.max(d)));
+        assert_eq!(
+            agglomerative(&data, Linkage::Average).merges[0].distance,
+            min
+        );
+        assert_eq!(
+            agglomerative(&data, Linkage::Complete).merges[58].distance,
+            max
+        );
+
+        let tree = Dendrogram {
+            n: 3,
+            merges: vec![
+                Merge {
+                    a: 0,
+                    b: 2,
+                    distance: 0.25,
+                    size: 2,
+                },
+                Merge {
+                    a: 1,
+                    b: 3,
+                    distance: 0.5,
+                    size: 3,
+                },
+            ],
+        };
+        assert_eq!(tree.to_newick(&["x", "y"]), "(y:0.5,(x:0.25,2:0.25):0.25);");
+        assert_eq!(tree.to_string().lines().count(), 3);
+    }
+}
@@ -1,69 +1,230 @@
 use crate::nearest;
 use crate::types::traits::{HyperVector, UnitAccumulator};
-use rand::Rng;
-use rand::prelude::IndexedRandom;
+use rand::{Rng, RngExt};
+use rayon::prelude::*;
 use std::borrow::Borrow;
 
+// Samples per parallel partial sum - merged in order, so the centroids do not depend
+// on the number of threads
+const CHUNK: usize = 256;
+
+// partial sums of a chunk, with the nearest centroid and its distance per sample
+type Partial<H> = (Vec<<H as HyperVector>::UnitAccumulator>, Vec<(usize, f32)>);
+
+// Relative decrease of the inertia below which training has converged
+const TOLERANCE: f64 = 1e-4;
+
+/// K-means over hypervectors: a centroid is the bundle of the samples nearest to it.
+///
+/// Inertia is the sum of the distances of the samples to their nearest centroid.
 pub struct KMeans<H: HyperVector> {
     pub k: usize,
     pub centroids: Vec<H>,
     pub counts: Vec<usize>,
+    /// Inertia of the centroids on the data trained on - infinite before training
+    pub inertia: f64,
+    /// Iterations of the last training
+    pub iterations: u32,
+    tolerance: f64,
 }
 
-impl<H: HyperVector> KMeans<H> {
-    /// Creates a new KMeans model with initial centroids chosen randomly.
-    pub fn new<T: Borrow<H>, R: Rng>(data: &[T], k: usize, rng: &mut R) -> Self {
+impl<H: HyperVector + Sync> KMeans<H> {
+    /// Creates a new KMeans model with initial centroids chosen by k-means++:
+    /// the first at random, each next one with probability proportional to the
+    /// squared distance to the nearest centroid chosen so far.
+    pub fn new<T, R>(data: &[T], k: usize, rng: &mut R) -> Self
+    where
+        T: Borrow<H> + Sync,
+        R: Rng + ?Sized,
+    {
         // Borrow means the function accepts slice of either HyperVector or reference to HyperVector...
         assert!(k > 0 && k <= data.len(), "k must be > 0 and <= data length");
 
-        // Simple random initialization.
-        // TODO: implement K-Means++ initialisation.
-        let centroids: Vec<H> = data.sample(rng, k).map(|v| v.borrow().clone()).collect();
+        let first = data[rng.random_range(0..data.len())].borrow().clone();
+        let mut dists: Vec<f64> = data
+            .par_iter()
+            .map(|v| v.borrow().distance(&first) as f64)
+            .collect();
+        let mut centroids = vec![first];
+
+        while centroids.len() < k {
+            let total: f64 = dists.iter().map(|d| d * d).sum();
+            let idx = if total > 0.0 {
+                let mut r = rng.random::<f64>() * total;
+                let mut idx = dists.len() - 1;
+                for (i, d) in dists.iter().enumerate() {
+                    r -= d * d;
+                    if r < 0.0 {
+                        idx = i;
+                        break;
+                    }
+                }
+                idx
+            } else {
+                // all samples coincide with a centroid
+                rng.random_range(0..data.len())
+            };
+            let c = data[idx].borrow().clone();
+            dists
+                .par_iter_mut()
+                .zip(data.par_iter())
+                .for_each(|(d, v)| *d = d.min(v.borrow().distance(&c) as f64));
+            centroids.push(c);
+        }
+
         Self {
             k,
             centroids,
             counts: vec![0; k],
+            inertia: f64::INFINITY,
+            iterations: 0,
+            tolerance: TOLERANCE,
+        }
+    }
+
+    /// Training stops when an iteration decreases the inertia by less than this
+    /// fraction - default 1e-4 - or when no sample changes cluster.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// The best of `n_init` k-means++ seeded runs - the one with the lowest inertia.
+    pub fn fit<T, R>(data: &[T], k: usize, n_init: usize, max_iters: u32, rng: &mut R) -> Self
+    where
+        T: Borrow<H> + Sync,
+        R: Rng + ?Sized,
+    {
+        let mut best: Option<Self> = None;
+        for _ in 0..n_init.max(1) {
+            let mut km = Self::new(data, k, rng);
+            km.train(data, max_iters, false);
+            if best.as_ref().is_none_or(|b| km.inertia < b.inertia) {
+                best = Some(km);
+            }
         }
+        best.unwrap()
     }
 
     /// Trains the model until convergence or max_iters is reached.
-    pub fn train<T: Borrow<H>>(&mut self, data: &[T], max_iters: u32, verbose: bool) -> usize {
-        let mut last_total_dist = usize::MAX;
+    /// Returns the inertia.
+    pub fn train<T: Borrow<H> + Sync>(&mut self, data: &[T], max_iters: u32, verbose: bool) -> f64 {
         if verbose {
             println!("Cluster {} examples", data.len());
         }
+        let mut assignments = vec![usize::MAX; data.len()];
+        let mut last_inertia = f64::INFINITY;
+        self.iterations = 0;
         for i in 1..=max_iters {
-            let total_dist = self.step(data);
+            let (inertia, changed) = self.step(data, &mut assignments);
+            self.iterations = i;
             if verbose {
-                println!("Iteration {i:3}: total distance = {total_dist:11}");
+                println!("Iteration {i:3}: inertia = {inertia:12.4}, {changed} reassigned");
             }
-            if last_total_dist == total_dist {
+            let converged =
+                last_inertia.is_finite() && last_inertia - inertia <= self.tolerance * last_inertia;
+            if changed == 0 || converged {
                 if verbose {
                     println!("Converged after {i} iterations.");
                 }
                 break;
             }
-            last_total_dist = total_dist;
+            last_inertia = inertia;
         }
-        last_total_dist
+        self.inertia = self.total_distance(data);
+        self.inertia
     }
 
-    fn step<T: Borrow<H>>(&mut self, data: &[T]) -> usize {
-        let mut accumulators: Vec<H::UnitAccumulator> =
-            (0..self.k).map(|_| H::UnitAccumulator::default()).collect();
+    // Assigns the samples to their nearest centroid, in parallel, and moves the
+    // centroids to the bundles of their samples. A cluster left empty is re-seeded
+    // with the sample farthest from its centroid - a different one for every empty
+    // cluster, taken from a cluster of more than one sample. Returns the inertia of the
+    // assignment and the number of samples that changed cluster.
+    fn step<T: Borrow<H> + Sync>(&mut self, data: &[T], assignments: &mut [usize]) -> (f64, usize) {
+        let new_accumulators = || -> Vec<H::UnitAccumulator> {
+            (0..self.k).map(|_| H::UnitAccumulator::default()).collect()
+        };
 
-        let total_dist: usize = data
-            .iter()
-            .map(|v| {
-                let (idx, dist) = self.nearest(v.borrow());
-                accumulators[idx].add(v.borrow());
-                dist as usize
+        // per-chunk partial sums and nearest centroids, in parallel
+        let partial: Vec<Partial<H>> = data
+            .par_chunks(CHUNK)
+            .map(|chunk| {
+                let mut accumulators = new_accumulators();
+                let mut nearest = Vec::with_capacity(chunk.len());
+                for v in chunk {
+                    let (idx, dist) = self.nearest(v.borrow());
+                    accumulators[idx].add(v.borrow());
+                    nearest.push((idx, dist));
+                }
+                (accumulators, nearest)
             })
-            .sum();
+            .collect();
+
+        let mut accumulators = new_accumulators();
+        let mut nearest = Vec::with_capacity(data.len());
+        for (part, part_nearest) in partial {
+            for (acc, p) in accumulators.iter_mut().zip(&part) {
+                acc.merge(p);
+            }
+            nearest.extend(part_nearest);
+        }
+
+        let mut changed = 0;
+        for (a, &(idx, _)) in assignments.iter_mut().zip(&nearest) {
+            if *a != idx {
+                *a = idx;
+                changed += 1;
+            }
+        }
+        let inertia = nearest.iter().map(|&(_, d)| d as f64).sum();
 
         self.centroids = accumulators.iter_mut().map(|a| a.finalize()).collect();
         self.counts = accumulators.iter().map(|a| a.count()).collect();
-        total_dist
+
+        let empty: Vec<usize> = (0..self.k).filter(|&c| self.counts[c] == 0).collect();
+        if !empty.is_empty() {
+            let mut farthest: Vec<usize> = (0..data.len()).collect();
+            farthest.sort_by(|&a, &b| nearest[b].1.total_cmp(&nearest[a].1).then(a.cmp(&b)));
+            let mut farthest = farthest.into_iter();
+            let mut donors = Vec::new();
+            for c in empty {
+                let Some(i) = farthest.find(|&i| self.counts[assignments[i]] > 1) else {
+                    break;
+                };
+                let donor = assignments[i];
+                self.counts[donor] -= 1;
+                self.counts[c] = 1;
+                assignments[i] = c;
+                self.centroids[c] = data[i].borrow().clone();
+                donors.push(donor);
+                changed += 1; // not converged yet
+            }
+            // the donors' centroids without the samples they gave away
+            donors.sort_unstable();
+            donors.dedup();
+            for d in donors {
+                let mut acc = H::UnitAccumulator::default();
+                for (v, _) in data.iter().zip(&*assignments).filter(|(_, a)| **a == d) {
+                    acc.add(v.borrow());
+                }
+                self.centroids[d] = acc.finalize();
+            }
+        }
+        (inertia, changed)
+    }
+
+    /// Sum of the distances of the samples to their nearest centroid
+    pub fn total_distance<T: Borrow<H> + Sync>(&self, data: &[T]) -> f64 {
+        data.par_iter()
+            .map(|v| self.nearest(v.borrow()).1 as f64)
+            .sum()
+    }
+
+    /// Index of the nearest centroid of every sample
+    pub fn assign<T: Borrow<H> + Sync>(&self, data: &[T]) -> Vec<usize> {
+        data.par_iter()
+            .map(|v| self.nearest(v.borrow()).0)
+            .collect()
     }
 
     /// Finds the index and distance of the nearest centroid to a given vector.
@@ -71,3 +232,49 @@ impl<H: HyperVector> KMeans<H> {
         nearest(hdv, &self.centroids)
     }
 }
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod kmeans_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_kmeans() {
+        let mut mt = MersenneTwister64::new(21);
+        let centers: Vec<Binary<16>> = (0..4).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..400).map(|i| i % 4).collect();
+        let data: Vec<Binary<16>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(150, &mut mt))
+            .collect();
+
+        // k-means++ seeds are samples, the centroids move in the first iteration
+        let mut km = KMeans::<Binary<16>>::new(&data, 4, &mut mt);
+        km.train(&data, 50, false);
+        assert!(km.iterations > 1);
+
+        let km = KMeans::fit(&data, 4, 3, 50, &mut mt);
+        assert_eq!(km.counts.iter().sum::<usize>(), 400);
+        // each cluster is one of the generating centers
+        let assigned = km.assign(&data);
+        for c in 0..4 {
+            let members: Vec<usize> = (0..400).filter(|&i| assigned[i] == c).collect();
+            assert_eq!(members.len(), 100);
+            assert!(members.iter().all(|&i| labels[i] == labels[members[0]]));
+        }
+        // ~150 of 1024 bits flipped per sample
+        assert!(km.inertia > 0.0 && km.inertia < 400.0 * 0.2);
+        assert!((km.inertia - km.total_distance(&data)).abs() < 1e-9);
+
+        // more clusters than distinct samples - the empty ones are re-seeded
+        let few: Vec<&Binary<16>> = data.iter().take(3).chain(data.iter().take(3)).collect();
+        let mut km = KMeans::<Binary<16>>::new(&few, 5, &mut mt);
+        km.train(&few, 10, false);
+        assert_eq!(km.centroids.len(), 5);
+        assert_eq!(km.counts.iter().sum::<usize>(), 6);
+        assert_eq!(km.inertia, 0.0);
+    }
+}
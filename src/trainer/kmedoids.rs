@@ -0,0 +1,236 @@
+use crate::trainer::model_selection::select;
+use crate::{HyperVector, distance_matrix, nearest};
+use rand::Rng;
+use rand::seq::index;
+use rayon::prelude::*;
+
+/// K-medoids over hypervectors: a cluster is represented by one of its samples, the
+/// medoid, rather than by a bundle.
+///
+/// Cost is the sum of the distances of the samples to their nearest medoid.
+pub struct KMedoids<H: HyperVector> {
+    pub k: usize,
+    pub medoids: Vec<H>,
+    /// Index of every medoid in the data fitted on
+    pub indices: Vec<usize>,
+    pub cost: f64,
+}
+
+impl<H: HyperVector + Sync> KMedoids<H> {
+    /// Partitioning Around Medoids on all pairwise distances. Deterministic, but
+    /// needs n² floats and O(n²) time per swap - see clara for larger data.
+    pub fn pam(data: &[H], k: usize, max_iters: u32) -> Self {
+        assert!(k > 0 && k <= data.len(), "k must be > 0 and <= data length");
+        let indices = pam(&distance_matrix(data), k, max_iters);
+        Self::from_indices(data, indices)
+    }
+
+    /// CLARA: PAM on `n_samples` random subsets of `sample_size` samples - each
+    /// including the best medoids so far - keeping the medoids with the lowest cost
+    /// on all data. 40 + 2k samples is the usual subset size.
+    pub fn clara<R: Rng + ?Sized>(
+        data: &[H],
+        k: usize,
+        n_samples: usize,
+        sample_size: usize,
+        max_iters: u32,
+        rng: &mut R,
+    ) -> Self {
+        let sample_size = sample_size.min(data.len());
+        assert!(
+            k > 0 && k <= sample_size,
+            "k must be > 0 and <= sample size"
+        );
+
+        let mut best: Option<Self> = None;
+        for _ in 0..n_samples.max(1) {
+            let mut subset = best.as_ref().map_or_else(Vec::new, |b| b.indices.clone());
+            for i in index::sample(rng, data.len(), sample_size) {
+                if subset.len() == sample_size {
+                    break;
+                }
+                if !subset.contains(&i) {
+                    subset.push(i);
+                }
+            }
+            subset.sort_unstable();
+
+            let medoids = pam(&distance_matrix(&select(data, &subset)), k, max_iters);
+            let km = Self::from_indices(data, medoids.iter().map(|&m| subset[m]).collect());
+            if best.as_ref().is_none_or(|b| km.cost < b.cost) {
+                best = Some(km);
+            }
+        }
+        best.unwrap()
+    }
+
+    fn from_indices(data: &[H], indices: Vec<usize>) -> Self {
+        let mut km = Self {
+            k: indices.len(),
+            medoids: select(data, &indices),
+            indices,
+            cost: 0.0,
+        };
+        km.cost = km.total_distance(data);
+        km
+    }
+
+    /// Sum of the distances of the samples to their nearest medoid
+    pub fn total_distance(&self, data: &[H]) -> f64 {
+        data.par_iter().map(|v| self.nearest(v).1 as f64).sum()
+    }
+
+    /// Index of the nearest medoid of every sample
+    pub fn assign(&self, data: &[H]) -> Vec<usize> {
+        data.par_iter().map(|v| self.nearest(v).0).collect()
+    }
+
+    /// Finds the index and distance of the nearest medoid to a given vector.
+    pub fn nearest(&self, hdv: &H) -> (usize, f32) {
+        nearest(hdv, &self.medoids)
+    }
+}
+
+// PAM on a distance matrix, returns the indices of the k medoids.
+//
+// BUILD picks the sample with the least total distance, then greedily the sample
+// that decreases the cost most. SWAP then replaces a medoid by a non-medoid as long
+// as the best such swap decreases the cost. The change of cost of a swap follows
+// from the distances of every sample to its nearest and second nearest medoid, so
+// an iteration takes O(n²).
+fn pam(dist: &[Vec<f32>], k: usize, max_iters: u32) -> Vec<usize> {
+    let n = dist.len();
+    // ties go to the lower index
+    let by_gain = |a: &(f64, usize), b: &(f64, usize)| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1));
+
+    let (_, first) = (0..n)
+        .into_par_iter()
+        .map(|i| (-dist[i].iter().map(|&d| d as f64).sum::<f64>(), i))
+        .max_by(by_gain)
+        .unwrap();
+    let mut medoids = vec![first];
+    let mut is_medoid = vec![false; n];
+    is_medoid[first] = true;
+    let mut nearest_dist = dist[first].clone();
+
+    while medoids.len() < k {
+        let (_, c) = (0..n)
+            .into_par_iter()
+            .filter(|&c| !is_medoid[c])
+            .map(|c| {
+                let gain = nearest_dist
+                    .iter()
+                    .zip(&dist[c])
+                    .map(|(&d, &dc)| (d - dc).max(0.0) as f64)
+                    .sum::<f64>();
+                (gain, c)
+            })
+            .max_by(by_gain)
+            .unwrap();
+        for (d, &dc) in nearest_dist.iter_mut().zip(&dist[c]) {
+            *d = d.min(dc);
+        }
+        medoids.push(c);
+        is_medoid[c] = true;
+    }
+
+    for _ in 0..max_iters {
+        // per sample: position of its nearest medoid, distance to it and to the second nearest
+        let near: Vec<(usize, f32, f32)> = (0..n)
+            .map(|j| {
+                let (mut first, mut second) = ((0, f32::INFINITY), f32::INFINITY);
+                for (m, &i) in medoids.iter().enumerate() {
+                    let d = dist[i][j];
+                    if d < first.1 {
+                        second = first.1;
+                        first = (m, d);
+                    } else if d < second {
+                        second = d;
+                    }
+                }
+                (first.0, first.1, second)
+            })
+            .collect();
+
+        // the change of cost of every swap of a medoid with candidate o
+        let (delta, o, m) = (0..n)
+            .into_par_iter()
+            .filter(|&o| !is_medoid[o])
+            .map(|o| {
+                // change when removing any medoid but the nearest, plus a correction for the nearest
+                let mut common = 0.0;
+                let mut removed = vec![0.0; k];
+                for (j, &(m, d, e)) in near.iter().enumerate() {
+                    let d_o = dist[o][j];
+                    let keep = (d_o.min(d) - d) as f64;
+                    common += keep;
+                    removed[m] += (d_o.min(e) - d) as f64 - keep;
+                }
+                let (m, r) = removed
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.total_cmp(b.1))
+                    .unwrap();
+                (common + r, o, m)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+            .unwrap_or((0.0, 0, 0));
+
+        if delta > -1e-9 {
+            break;
+        }
+        is_medoid[medoids[m]] = false;
+        is_medoid[o] = true;
+        medoids[m] = o;
+    }
+    medoids
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod kmedoids_tests {
+    use super::*;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_kmedoids() {
+        let mut mt = MersenneTwister64::new(17);
+        let centers: Vec<Binary<8>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..150).map(|i| i % 3).collect();
+        let data: Vec<Binary<8>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(80, &mut mt))
+            .collect();
+
+        let pam = KMedoids::pam(&data, 3, 100);
+        let assigned = pam.assign(&data);
+        for (m, &i) in pam.indices.iter().enumerate() {
+            assert_eq!(assigned[i], m);
+            // each cluster is one of the generating centers
+            assert!((0..150).all(|j| (assigned[j] == m) == (labels[j] == labels[i])));
+        }
+        assert!((pam.cost - pam.total_distance(&data)).abs() < 1e-9);
+        // no single swap improves on PAM
+        let dist = distance_matrix(&data);
+        let cost = |medoids: &[usize]| -> f64 {
+            (0..150)
+                .map(|j| medoids.iter().map(|&m| dist[m][j]).fold(f32::MAX, f32::min) as f64)
+                .sum()
+        };
+        for m in 0..3 {
+            for o in (0..150).step_by(7) {
+                let mut swapped = pam.indices.clone();
+                swapped[m] = o;
+                assert!(cost(&swapped) >= cost(&pam.indices) - 1e-6);
+            }
+        }
+
+        let clara = KMedoids::clara(&data, 3, 5, 46, 100, &mut mt);
+        assert_eq!(clara.indices.len(), 3);
+        assert!(clara.cost >= pam.cost - 1e-6);
+        // ~80 of 512 bits flipped in both a sample and its medoid
+        assert!(clara.cost < 150.0 * 0.3);
+    }
+}
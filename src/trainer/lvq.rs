@@ -1,9 +1,15 @@
-use crate::trainer::{EpochResult, MultiPrototypeModel, Trainer, kmeans::KMeans};
+use crate::trainer::{
+    EpochResult, MultiPrototypeModel, Schedule, Trainer, check_labels, class_accumulators,
+    driver::Driver, kmeans::KMeans, par_accumulate,
+};
 use crate::{Accumulator, HyperVector, nearest, nearest_two};
 use rand::Rng;
 use rand::prelude::SliceRandom;
 use rayon::prelude::*;
 
+// k-means restarts per class for the initial prototypes
+const N_INIT: usize = 3;
+
 /// Perceptron trainer for HDV multi prototype classifiers.
 ///
 /// # Type parameters
@@ -23,6 +29,7 @@ where
     proto_per_class: usize,
     indices: Vec<usize>,
     rng: R,
+    schedule: Schedule,
     window: f32, // typically from the range 0.2 - 0.3
 }
 
@@ -43,37 +50,37 @@ where
     where
         L: Into<usize> + Copy + Send + Sync,
     {
+        check_labels(class_labels, n_classes);
         let total_prototypes = n_classes * proto_per_class;
-        let mut accumulators: Vec<T::Accumulator> = (0..total_prototypes)
-            .map(|_| T::Accumulator::new())
-            .collect();
 
-        // Build kmeans per class to get initial prototypes
+        // Build kmeans per class to get initial prototypes, without the excluded sample
         let mut prototypes: Vec<T> = Vec::with_capacity(total_prototypes);
         for class in 0..n_classes {
             let class_samples: Vec<&T> = samples
                 .iter()
                 .zip(class_labels.iter().copied())
-                .filter(|(_, l)| (*l).into() == class)
-                .map(|(h, _)| h)
+                .enumerate()
+                .filter(|&(i, (_, l))| Some(i) != exclude && l.into() == class)
+                .map(|(_, (h, _))| h)
                 .collect();
-            let mut km = KMeans::new(&class_samples, proto_per_class, &mut rng);
-            km.train(&class_samples, 100, false);
+            let km = KMeans::fit(&class_samples, proto_per_class, N_INIT, 100, &mut rng);
             prototypes.extend(km.centroids);
         }
 
         // Assign each sample to nearest prototype within its class
         // and compute new prototype-scoped label
-        let mut proto_labels: Vec<usize> = Vec::with_capacity(samples.len());
-        for (hdv, class_label) in samples.iter().zip(class_labels.iter()) {
-            let class = (*class_label).into();
-            let class_start = class * proto_per_class;
-            let (nearest_idx, _) =
-                nearest(hdv, &prototypes[class_start..class_start + proto_per_class]);
-            let proto_label = class_start + nearest_idx;
-            accumulators[proto_label].add(hdv, 1.0);
-            proto_labels.push(proto_label);
-        }
+        let proto_labels: Vec<usize> = samples
+            .par_iter()
+            .zip(class_labels.par_iter())
+            .map(|(hdv, class_label)| {
+                let class = (*class_label).into();
+                let class_start = class * proto_per_class;
+                let (nearest_idx, _) =
+                    nearest(hdv, &prototypes[class_start..class_start + proto_per_class]);
+                class_start + nearest_idx
+            })
+            .collect();
+        let accumulators = class_accumulators(samples, &proto_labels, exclude, total_prototypes);
 
         //let indices = (0..samples.len()).collect();
         let indices = (0..samples.len()).filter(|i| Some(*i) != exclude).collect();
@@ -86,19 +93,27 @@ where
             proto_per_class,
             indices,
             rng,
+            schedule: Schedule::default(),
             window,
         }
     }
 
+    /// Learning rate schedule, default lr 1 / sqrt(epoch)
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
     /// Run a single training epoch (LVQ2.1).
     ///
     /// Shuffles the sample order, finds all misclassifications in parallel,
-    /// then applies weight updates sequentially.
+    /// then applies the weight updates as merged per-thread partial sums.
     ///
-    /// `epoch` is 1-based and used to compute the learning rate `1/sqrt(epoch)`.
+    /// `epoch` is 1-based and used to compute the learning rate from the schedule,
+    /// by default `1/sqrt(epoch)`.
     pub fn step(&mut self, epoch: usize) -> EpochResult {
         self.indices.shuffle(&mut self.rng); // not needed for batch training...
-        let lr = 1.0 / (epoch as f64).sqrt();
+        let lr = self.schedule.rate(epoch);
 
         // Parallel: collect misclassifications
         // batch training rather than online where we update after every sample
@@ -131,27 +146,46 @@ where
             })
             .collect();
 
-        // Sequential: apply updates (accumulators not thread-safe)
-        for (idx, correct_idx, wrong_idx) in one_correct {
-            let hdv = &self.samples[idx];
-            self.accumulators[correct_idx].add(hdv, lr);
-            self.accumulators[wrong_idx].add(hdv, -lr);
-        }
+        // Parallel partial sums of the updates, merged into the accumulators
+        let updates: Vec<(usize, usize, f64)> = one_correct
+            .iter()
+            .flat_map(|&(idx, correct_idx, wrong_idx)| {
+                [(idx, correct_idx, lr), (idx, wrong_idx, -lr)]
+            })
+            .collect();
+        par_accumulate(&mut self.accumulators, self.samples, &updates);
 
         self.prototypes = self.accumulators.iter_mut().map(|a| a.finalize()).collect();
 
-        // Compute actual error count against all samples
-        let errors: usize = self
+        // errors of the updated prototypes against all samples
+        self.score(epoch)
+    }
+
+    /// Run up to `max_epochs` training steps, stopping early if zero errors.
+    /// LVQ2.1 has no covergence guarantee and can fluctuate, so the model of the
+    /// epoch with the fewest training errors is returned - see Driver for early
+    /// stopping on a validation set.
+    ///
+    /// Returns the results of each epoch and the trained model.
+    pub fn fit(self, max_epochs: usize) -> (MultiPrototypeModel<T>, Vec<EpochResult>) {
+        let fitted = Driver::new(max_epochs).run(self);
+        let history = fitted.history();
+        (fitted.model, history)
+    }
+
+    /// Scores the current prototypes on the training samples - a sample is
+    /// correct when its nearest prototype is one of its class
+    pub fn score(&self, epoch: usize) -> EpochResult {
+        let (samples, prototypes) = (self.samples, &self.prototypes);
+        let (proto_labels, proto_per_class) = (&self.proto_labels, self.proto_per_class);
+        let errors = self
             .indices
             .par_iter()
             .filter(|&&idx| {
-                let hdv = &self.samples[idx];
-                let true_class = self.proto_labels[idx] / self.proto_per_class;
-                let (p1_idx, _) = nearest(hdv, &self.prototypes);
-                p1_idx / self.proto_per_class != true_class
+                let (p1_idx, _) = nearest(&samples[idx], prototypes);
+                p1_idx / proto_per_class != proto_labels[idx] / proto_per_class
             })
             .count();
-
         EpochResult {
             epoch,
             correct: self.indices.len() - errors,
@@ -159,35 +193,18 @@ where
         }
     }
 
-    /// Run up to `max_epochs` training steps, stopping early if zero errors.
-    /// Note that LVQ2.1 has no covergence guarantee and can fluctuate
-    ///
-    /// Returns the results of each epoch and the trained model.
-    pub fn fit(mut self, max_epochs: usize) -> (MultiPrototypeModel<T>, Vec<EpochResult>) {
-        let mut history = Vec::with_capacity(max_epochs);
-        for epoch in 1..=max_epochs {
-            let result = self.step(epoch);
-            history.push(result);
-        }
-        (
-            MultiPrototypeModel {
-                prototypes: self.prototypes,
-                proto_labels: self.proto_labels,
-                n_classes: self.n_classes,
-                proto_per_class: self.proto_per_class,
-            },
-            history,
+    /// The model as trained so far
+    pub fn model(&self) -> MultiPrototypeModel<T> {
+        MultiPrototypeModel::new(
+            self.prototypes.clone(),
+            self.n_classes,
+            self.proto_per_class,
         )
     }
 
     /// Consume the trainer and return the final trained model.
     pub fn into_model(self) -> MultiPrototypeModel<T> {
-        MultiPrototypeModel {
-            prototypes: self.prototypes,
-            proto_labels: self.proto_labels,
-            n_classes: self.n_classes,
-            proto_per_class: self.proto_per_class,
-        }
+        MultiPrototypeModel::new(self.prototypes, self.n_classes, self.proto_per_class)
     }
 }
 
@@ -201,6 +218,14 @@ where
         self.step(epoch)
     }
 
+    fn score(&self, epoch: usize) -> EpochResult {
+        self.score(epoch)
+    }
+
+    fn model(&self) -> Self::Model {
+        self.model()
+    }
+
     fn into_model(self) -> Self::Model {
         self.into_model()
     }
@@ -0,0 +1,360 @@
+use crate::HyperVector;
+use crate::trainer::Classifier;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ── Splits ────────────────────────────────────────────────────────────────────
+//
+// A split is a list of folds, each the sample indices to train on and to test on.
+// The indices of a fold are sorted, so a fold does not depend on the shuffling
+// order within it.
+
+/// Sample indices to train and to test on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+impl Fold {
+    /// The fold testing on `test` and training on the rest of the n samples
+    pub fn from_test(n: usize, mut test: Vec<usize>) -> Self {
+        test.sort_unstable();
+        let mut in_test = vec![false; n];
+        for &i in &test {
+            in_test[i] = true;
+        }
+        let train = (0..n).filter(|&i| !in_test[i]).collect();
+        Self { train, test }
+    }
+}
+
+/// The items at the indices, e.g. the samples or labels of a fold
+pub fn select<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| items[i].clone()).collect()
+}
+
+/// k folds of shuffled samples
+pub fn k_fold<R: Rng + ?Sized>(n: usize, k: usize, rng: &mut R) -> Vec<Fold> {
+    assert!(k >= 2 && k <= n, "k must be >= 2 and <= number of samples");
+    let mut indices: Vec<usize> = (0..n).collect();
+    indices.shuffle(rng);
+    let mut tests = vec![Vec::new(); k];
+    for (j, i) in indices.into_iter().enumerate() {
+        tests[j % k].push(i);
+    }
+    tests.into_iter().map(|t| Fold::from_test(n, t)).collect()
+}
+
+/// k folds with the classes in about the same proportions as in the labels
+pub fn stratified_k_fold<L, R>(labels: &[L], k: usize, rng: &mut R) -> Vec<Fold>
+where
+    L: Into<usize> + Copy,
+    R: Rng + ?Sized,
+{
+    let n = labels.len();
+    assert!(k >= 2 && k <= n, "k must be >= 2 and <= number of samples");
+    let mut tests = vec![Vec::new(); k];
+    // dealing on across classes keeps the fold sizes within one of each other
+    let mut next = 0;
+    for mut class in by_class(labels) {
+        class.shuffle(rng);
+        for i in class {
+            tests[next % k].push(i);
+            next += 1;
+        }
+    }
+    tests.into_iter().map(|t| Fold::from_test(n, t)).collect()
+}
+
+/// One fold per sample, testing on that sample
+pub fn leave_one_out(n: usize) -> Vec<Fold> {
+    (0..n).map(|i| Fold::from_test(n, vec![i])).collect()
+}
+
+/// k folds that keep the samples of a group - e.g. a subject - together, so a
+/// model is never tested on a group it was trained on. Deterministic: the largest
+/// groups go first, each to the fold with the fewest test samples.
+pub fn group_k_fold<G: Ord + Copy>(groups: &[G], k: usize) -> Vec<Fold> {
+    let mut members: BTreeMap<G, Vec<usize>> = BTreeMap::new();
+    for (i, &g) in groups.iter().enumerate() {
+        members.entry(g).or_default().push(i);
+    }
+    assert!(
+        k >= 2 && k <= members.len(),
+        "k must be >= 2 and <= number of groups"
+    );
+    let mut members: Vec<Vec<usize>> = members.into_values().collect();
+    members.sort_by_key(|m| std::cmp::Reverse(m.len())); // stable - ties in group order
+
+    let mut tests = vec![Vec::new(); k];
+    for m in members {
+        let fold = (0..k).min_by_key(|&f| tests[f].len()).unwrap();
+        tests[fold].extend(m);
+    }
+    tests
+        .into_iter()
+        .map(|t| Fold::from_test(groups.len(), t))
+        .collect()
+}
+
+/// n_splits random train/test splits, each testing on test_fraction of every class
+pub fn stratified_shuffle_split<L, R>(
+    labels: &[L],
+    n_splits: usize,
+    test_fraction: f64,
+    rng: &mut R,
+) -> Vec<Fold>
+where
+    L: Into<usize> + Copy,
+    R: Rng + ?Sized,
+{
+    assert!(test_fraction > 0.0 && test_fraction < 1.0);
+    let classes = by_class(labels);
+    (0..n_splits)
+        .map(|_| {
+            let mut test = Vec::new();
+            for class in &classes {
+                let mut class = class.clone();
+                class.shuffle(rng);
+                let n_test = (class.len() as f64 * test_fraction).round() as usize;
+                test.extend_from_slice(&class[..n_test]);
+            }
+            Fold::from_test(labels.len(), test)
+        })
+        .collect()
+}
+
+// sample indices of each class, in class order
+fn by_class<L: Into<usize> + Copy>(labels: &[L]) -> Vec<Vec<usize>> {
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    for (i, &l) in labels.iter().enumerate() {
+        let l = l.into();
+        if l >= classes.len() {
+            classes.resize(l + 1, Vec::new());
+        }
+        classes[l].push(i);
+    }
+    classes
+}
+
+// ── Cross-validation ──────────────────────────────────────────────────────────
+
+/// Test result of one fold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldResult {
+    pub fold: usize,
+    pub correct: usize,
+    pub errors: usize,
+}
+
+impl FoldResult {
+    pub fn total(&self) -> usize {
+        self.correct + self.errors
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        self.correct as f64 / self.total() as f64
+    }
+}
+
+/// Per-fold results of a cross-validation
+#[derive(Debug, Clone)]
+pub struct CvReport {
+    pub folds: Vec<FoldResult>,
+}
+
+impl CvReport {
+    pub fn accuracies(&self) -> Vec<f64> {
+        self.folds.iter().map(|f| f.accuracy()).collect()
+    }
+
+    pub fn summary(&self) -> Summary {
+        Summary::new(&self.accuracies()).expect("no folds")
+    }
+
+    /// Accuracy over the test samples of all folds
+    pub fn pooled_accuracy(&self) -> f64 {
+        let correct: usize = self.folds.iter().map(|f| f.correct).sum();
+        let total: usize = self.folds.iter().map(|f| f.total()).sum();
+        correct as f64 / total as f64
+    }
+}
+
+impl fmt::Display for CvReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in &self.folds {
+            writeln!(
+                f,
+                "Fold {:2}: {:.2}%  ({}/{})",
+                r.fold + 1,
+                100.0 * r.accuracy(),
+                r.correct,
+                r.total()
+            )?;
+        }
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Trains a model per fold - in parallel - and tests it on the fold.
+///
+/// `fit` gets the fold index, e.g. to seed an rng, and the training samples and
+/// labels of the fold.
+pub fn cross_validate<T, L, M, F>(samples: &[T], labels: &[L], folds: &[Fold], fit: F) -> CvReport
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    M: Classifier<T> + Sync,
+    F: Fn(usize, &[T], &[L]) -> M + Sync,
+{
+    assert_eq!(samples.len(), labels.len());
+    let folds = folds
+        .par_iter()
+        .enumerate()
+        .map(|(fold, Fold { train, test })| {
+            let model = fit(fold, &select(samples, train), &select(labels, train));
+            let (correct, errors, _) =
+                model.accuracy(&select(samples, test), &select(labels, test));
+            FoldResult {
+                fold,
+                correct,
+                errors,
+            }
+        })
+        .collect();
+    CvReport { folds }
+}
+
+/// Mean, standard deviation, min and max of accuracies - displayed in percent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    /// Sample standard deviation, 0 for a single value
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let var = if values.len() > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        Some(Self {
+            mean,
+            std: var.sqrt(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "avg {:.2}% ± {:.2}, min {:.2}%, max {:.2}%",
+            100.0 * self.mean,
+            100.0 * self.std,
+            100.0 * self.min,
+            100.0 * self.max
+        )
+    }
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod model_selection_tests {
+    use super::*;
+    use crate::trainer::perceptron::DynPerceptronTrainer;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    // every sample is tested exactly once, and never trained on in its fold
+    fn check_partition(folds: &[Fold], n: usize) {
+        let mut tested = vec![0; n];
+        for fold in folds {
+            assert_eq!(fold.train.len() + fold.test.len(), n);
+            for &i in &fold.test {
+                tested[i] += 1;
+                assert!(fold.train.binary_search(&i).is_err());
+            }
+        }
+        assert!(tested.iter().all(|&t| t == 1));
+    }
+
+    #[test]
+    fn test_splits() {
+        let mut mt = MersenneTwister64::new(5);
+        let labels: Vec<usize> = (0..100).map(|i| if i < 70 { 0 } else { 1 }).collect();
+
+        let folds = k_fold(100, 3, &mut mt);
+        check_partition(&folds, 100);
+
+        let folds = stratified_k_fold(&labels, 5, &mut mt);
+        check_partition(&folds, 100);
+        for fold in &folds {
+            let ones = fold.test.iter().filter(|&&i| labels[i] == 1).count();
+            assert_eq!((fold.test.len(), ones), (20, 6));
+        }
+
+        check_partition(&leave_one_out(7), 7);
+
+        let groups: Vec<u8> = (0..100).map(|i| (i % 10) as u8).collect();
+        let folds = group_k_fold(&groups, 4);
+        check_partition(&folds, 100);
+        for fold in &folds {
+            for &i in &fold.test {
+                assert!(fold.train.iter().all(|&j| groups[j] != groups[i]));
+            }
+        }
+        assert_eq!(folds, group_k_fold(&groups, 4));
+
+        let splits = stratified_shuffle_split(&labels, 3, 0.2, &mut mt);
+        assert_eq!(splits.len(), 3);
+        for fold in &splits {
+            let ones = fold.test.iter().filter(|&&i| labels[i] == 1).count();
+            assert_eq!((fold.test.len(), ones), (20, 6));
+        }
+    }
+
+    #[test]
+    fn test_cross_validate() {
+        let mut mt = MersenneTwister64::new(9);
+        let centers: Vec<Binary<4>> = (0..3).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..90).map(|i| i % 3).collect();
+        let samples: Vec<Binary<4>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(60, &mut mt))
+            .collect();
+
+        let folds = stratified_k_fold(&labels, 3, &mut mt);
+        let report = cross_validate(&samples, &labels, &folds, |fold, train, labels| {
+            let rng = MersenneTwister64::new(fold as u64);
+            DynPerceptronTrainer::new(train, labels, None, 3, rng)
+                .fit(5)
+                .0
+        });
+        assert_eq!(report.folds.len(), 3);
+        assert_eq!(report.folds.iter().map(|f| f.total()).sum::<usize>(), 90);
+        assert_eq!(report.pooled_accuracy(), 1.0);
+        assert_eq!(report.summary().std, 0.0);
+
+        let s = Summary::new(&[0.5, 0.7, 0.9]).unwrap();
+        assert!((s.mean - 0.7).abs() < 1e-12 && (s.std - 0.2).abs() < 1e-12);
+        assert_eq!((s.min, s.max), (0.5, 0.9));
+        assert_eq!(Summary::new(&[]), None);
+    }
+}
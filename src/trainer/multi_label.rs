@@ -0,0 +1,228 @@
+use crate::HyperVector;
+use crate::metrics::MultiLabelMetrics;
+use crate::trainer::Classifier;
+use rayon::prelude::*;
+
+// ── Binary relevance ──────────────────────────────────────────────────────────
+//
+// A multi-label problem as one binary problem per label - the sample has the label
+// (class 1) or not (class 0) - each learned by any of the classifiers here. A label
+// is predicted when its classifier scores class 1 above class 0 by more than the
+// label's threshold. Label sets are label indices, in any order.
+
+/// One binary classifier and threshold per label
+pub struct MultiLabelModel<M> {
+    pub models: Vec<M>,
+    /// 0 - the binary prediction - until tuned
+    pub thresholds: Vec<f32>,
+}
+
+/// Trains a binary classifier per label, in parallel.
+///
+/// `fit` gets the label index, e.g. to seed an rng, the samples and their 0/1
+/// labels - 1 for the samples with the label.
+pub fn binary_relevance<T, S, M, F>(
+    samples: &[T],
+    labels: &[S],
+    n_labels: usize,
+    fit: F,
+) -> MultiLabelModel<M>
+where
+    T: HyperVector + Send + Sync,
+    S: AsRef<[usize]> + Sync,
+    M: Classifier<T> + Send,
+    F: Fn(usize, &[T], &[usize]) -> M + Sync,
+{
+    assert_eq!(samples.len(), labels.len());
+    let models = (0..n_labels)
+        .into_par_iter()
+        .map(|j| {
+            let has: Vec<usize> = labels
+                .iter()
+                .map(|l| l.as_ref().contains(&j) as usize)
+                .collect();
+            fit(j, samples, &has)
+        })
+        .collect();
+    MultiLabelModel {
+        models,
+        thresholds: vec![0.0; n_labels],
+    }
+}
+
+impl<M> MultiLabelModel<M> {
+    pub fn n_labels(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Per-label score: the class 1 score minus the class 0 score
+    pub fn scores<T: HyperVector>(&self, h: &T) -> Vec<f32>
+    where
+        M: Classifier<T>,
+    {
+        self.models
+            .iter()
+            .map(|m| {
+                let s = m.scores(h);
+                s[1] - s[0]
+            })
+            .collect()
+    }
+
+    /// The labels scoring above their threshold, in label order
+    pub fn predict<T: HyperVector>(&self, h: &T) -> Vec<usize>
+    where
+        M: Classifier<T>,
+    {
+        self.scores(h)
+            .iter()
+            .zip(&self.thresholds)
+            .enumerate()
+            .filter(|(_, (s, t))| s > t)
+            .map(|(j, _)| j)
+            .collect()
+    }
+
+    pub fn predict_all<T>(&self, samples: &[T]) -> Vec<Vec<usize>>
+    where
+        T: HyperVector + Send + Sync,
+        M: Classifier<T> + Sync,
+    {
+        samples.par_iter().map(|h| self.predict(h)).collect()
+    }
+
+    /// Sets the threshold of every label to the one with the best F1 on these
+    /// samples. Tune on held-out samples - the training samples are usually
+    /// separated better than new ones.
+    pub fn tune_thresholds<T, S>(&mut self, samples: &[T], labels: &[S])
+    where
+        T: HyperVector + Send + Sync,
+        S: AsRef<[usize]>,
+        M: Classifier<T> + Sync,
+    {
+        assert!(!samples.is_empty() && samples.len() == labels.len());
+        let scores: Vec<Vec<f32>> = samples.par_iter().map(|h| self.scores(h)).collect();
+        for j in 0..self.n_labels() {
+            let mut ranked: Vec<(f32, bool)> = scores
+                .iter()
+                .zip(labels)
+                .map(|(s, l)| (s[j], l.as_ref().contains(&j)))
+                .collect();
+            ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+            self.thresholds[j] = best_threshold(&ranked);
+        }
+    }
+
+    /// Hamming loss, subset accuracy and F1 of the predictions
+    pub fn evaluate<T, S>(&self, samples: &[T], labels: &[S]) -> MultiLabelMetrics
+    where
+        T: HyperVector + Send + Sync,
+        S: AsRef<[usize]>,
+        M: Classifier<T> + Sync,
+    {
+        MultiLabelMetrics::from_predictions(&self.predict_all(samples), labels, self.n_labels())
+    }
+}
+
+// The threshold with the best F1 for scores sorted in decreasing order, each with
+// whether the sample has the label. Predicting the top k samples has F1
+// 2 tp / (2 tp + fp + fn); the threshold is halfway between the k-th score and the
+// next lower one. Ties go to the higher threshold.
+fn best_threshold(ranked: &[(f32, bool)]) -> f32 {
+    let positives = ranked.iter().filter(|r| r.1).count();
+    let mut best = (0.0, ranked[0].0); // predicting nothing
+    let mut tp = 0;
+    for k in 1..=ranked.len() {
+        tp += ranked[k - 1].1 as usize;
+        let next = ranked.get(k).map(|r| r.0);
+        if next == Some(ranked[k - 1].0) {
+            continue; // a threshold cannot split tied scores
+        }
+        let f1 = 2.0 * tp as f64 / (k + positives) as f64;
+        if f1 > best.0 {
+            let low = next.unwrap_or(ranked[k - 1].0 - 1.0);
+            best = (f1, (ranked[k - 1].0 + low) / 2.0);
+        }
+    }
+    best.1
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod multi_label_tests {
+    use super::*;
+    use crate::UnitAccumulator;
+    use crate::trainer::multi_perceptron::PerceptronMultiTrainer;
+    use crate::trainer::perceptron::DynPerceptronTrainer;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+    use rand::RngExt;
+
+    #[test]
+    fn test_binary_relevance() {
+        let mut mt = MersenneTwister64::new(30);
+        let label_hvs: Vec<Binary<8>> = (0..5).map(|_| Binary::random(&mut mt)).collect();
+        // 1 to 3 labels per sample, a sample is the bundle of its labels and noise
+        let labels: Vec<Vec<usize>> = (0..500)
+            .map(|_| {
+                let mut set: Vec<usize> = (0..5).filter(|_| mt.random::<f32>() < 0.3).collect();
+                if set.is_empty() {
+                    set.push(mt.random_range(0..5));
+                }
+                set
+            })
+            .collect();
+        let samples: Vec<Binary<8>> = labels
+            .iter()
+            .map(|set| {
+                let mut acc = <Binary<8> as HyperVector>::UnitAccumulator::default();
+                for &l in set {
+                    acc.add(&label_hvs[l]);
+                }
+                acc.add(&Binary::random(&mut mt));
+                acc.finalize()
+            })
+            .collect();
+        // thresholds are tuned on valid and evaluated on test
+        let (train, rest) = samples.split_at(300);
+        let (train_labels, rest_labels) = labels.split_at(300);
+        let (valid, test) = rest.split_at(100);
+        let (valid_labels, test_labels) = rest_labels.split_at(100);
+
+        let mut model = binary_relevance(train, train_labels, 5, |j, samples, has| {
+            let rng = MersenneTwister64::new(j as u64);
+            DynPerceptronTrainer::new(samples, has, None, 2, rng)
+                .fit(20)
+                .0
+        });
+        assert_eq!(model.n_labels(), 5);
+        model.tune_thresholds(valid, valid_labels);
+        let after = model.evaluate(test, test_labels);
+        assert!(
+            after.micro_avg().f1 > 0.8 && after.hamming_loss() < 0.1,
+            "{after}"
+        );
+        assert!(model.predict(&test[0]).iter().all(|&j| j < 5));
+
+        // binary relevance of multi prototype models - a score per class
+        let multi = binary_relevance(train, train_labels, 5, |j, samples, has| {
+            let rng = MersenneTwister64::new(j as u64);
+            PerceptronMultiTrainer::new(samples, has, None, 2, 2, rng)
+                .fit(20)
+                .0
+        });
+        let metrics = multi.evaluate(test, test_labels);
+        assert!(metrics.micro_avg().f1 > 0.8, "{metrics}");
+
+        // F1 is best predicting the top 2 of these
+        let ranked = [
+            (0.9, true),
+            (0.5, true),
+            (0.0, false),
+            (-0.3, false),
+            (-0.4, true),
+        ];
+        assert_eq!(best_threshold(&ranked), 0.25);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::trainer::{EpochResult, PrototypeModel, Trainer};
+use crate::trainer::{
+    DynPrototypeModel, EpochResult, PrototypeModel, Trainer, class_accumulators, par_accumulate,
+};
 use crate::{Accumulator, HyperVector, nearest};
 use rand::Rng;
 use rand::prelude::SliceRandom;
+use rayon::prelude::*;
 
 /// Passive-Aggressive trainer, variants PA, PA-I, and PA-II.
 ///
@@ -37,22 +40,125 @@ impl PaVariant {
     }
 }
 
+/// See DynPaTrainer when the number of classes is known only at runtime.
 pub struct PaTrainer<'a, T, L, R, const N: usize>
 where
     T: HyperVector + Send + Sync,
     L: Into<usize> + Copy + Send + Sync,
     R: Rng,
 {
-    accumulators: [T::Accumulator; N],
-    prototypes: [T; N],
+    inner: DynPaTrainer<'a, T, L, R>,
+}
+
+impl<'a, T, L, R, const N: usize> PaTrainer<'a, T, L, R, N>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    pub fn new(
+        hvs: &'a [T],
+        labels: &'a [L],
+        exclude: Option<usize>,
+        variant: PaVariant,
+        rng: R,
+    ) -> Self {
+        Self {
+            inner: DynPaTrainer::new(hvs, labels, exclude, N, variant, rng),
+        }
+    }
+
+    /// Mini-batch mode, see DynPaTrainer::with_batch_size
+    pub fn with_batch_size(mut self, size: Option<usize>) -> Self {
+        self.inner = self.inner.with_batch_size(size);
+        self
+    }
+
+    /// One PA epoch, see DynPaTrainer::step
+    pub fn step(&mut self, epoch: usize) -> EpochResult {
+        self.inner.step(epoch)
+    }
+
+    pub fn score(&self, epoch: usize) -> EpochResult {
+        self.inner.score(epoch)
+    }
+
+    pub fn fit(mut self, max_epochs: usize) -> (PrototypeModel<T, N>, Vec<EpochResult>) {
+        let mut history = Vec::with_capacity(max_epochs);
+        for epoch in 1..=max_epochs {
+            let result = self.step(epoch);
+            history.push(result);
+            if result.errors == 0 {
+                // TODO - can still increase the margin...
+                break;
+            }
+        }
+        (self.into_model(), history)
+    }
+
+    pub fn prototypes(&self) -> &[T; N] {
+        let Ok(prototypes) = self.inner.prototypes().try_into() else {
+            unreachable!()
+        };
+        prototypes
+    }
+
+    pub fn model(&self) -> PrototypeModel<T, N> {
+        PrototypeModel {
+            prototypes: self.prototypes().clone(),
+        }
+    }
+
+    pub fn into_model(self) -> PrototypeModel<T, N> {
+        let Ok(prototypes) = self.inner.prototypes.try_into() else {
+            unreachable!()
+        };
+        PrototypeModel { prototypes }
+    }
+}
+
+impl<'a, T, L, R, const N: usize> Trainer<T> for PaTrainer<'a, T, L, R, N>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    type Model = PrototypeModel<T, N>;
+    fn step(&mut self, epoch: usize) -> EpochResult {
+        self.step(epoch)
+    }
+
+    fn score(&self, epoch: usize) -> EpochResult {
+        self.score(epoch)
+    }
+
+    fn model(&self) -> Self::Model {
+        self.model()
+    }
+
+    fn into_model(self) -> Self::Model {
+        self.into_model()
+    }
+}
+
+/// PaTrainer with the number of classes given at runtime
+pub struct DynPaTrainer<'a, T, L, R>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    accumulators: Vec<T::Accumulator>,
+    prototypes: Vec<T>,
     samples: &'a [T],
     labels: &'a [L],
     indices: Vec<usize>,
     rng: R,
     variant: PaVariant,
+    batch_size: Option<usize>, // None: online
 }
 
-impl<'a, T, L, R, const N: usize> PaTrainer<'a, T, L, R, N>
+impl<'a, T, L, R> DynPaTrainer<'a, T, L, R>
 where
     T: HyperVector + Send + Sync,
     L: Into<usize> + Copy + Send + Sync,
@@ -62,19 +168,15 @@ where
         hvs: &'a [T],
         labels: &'a [L],
         exclude: Option<usize>,
+        n_classes: usize,
         variant: PaVariant,
         rng: R,
     ) -> Self {
         let n = hvs.len();
         assert_eq!(n, labels.len());
 
-        let mut accumulators: [T::Accumulator; N] = core::array::from_fn(|_| T::Accumulator::new());
-
-        for (hdv, label) in hvs.iter().zip(labels.iter()) {
-            accumulators[(*label).into()].add(hdv, 1.0);
-        }
-
-        let prototypes: [T; N] = core::array::from_fn(|i| accumulators[i].finalize());
+        let mut accumulators = class_accumulators(hvs, labels, exclude, n_classes);
+        let prototypes: Vec<T> = accumulators.iter_mut().map(|acc| acc.finalize()).collect();
         let indices = (0..hvs.len()).filter(|i| Some(*i) != exclude).collect();
 
         Self {
@@ -85,9 +187,23 @@ where
             indices,
             rng,
             variant,
+            batch_size: None,
         }
     }
 
+    /// Mini-batch mode: the losses and rivals of a batch of `size` samples are
+    /// computed in parallel against the prototypes at the start of the batch, and
+    /// the prototypes are updated once per batch. A batch of all samples is plain
+    /// batch training. Much faster than the default online mode - one finalize per
+    /// class and batch instead of two per margin violation - but a sample no longer
+    /// sees the updates of the samples just before it, so it may need more epochs.
+    /// None is online mode, the default.
+    pub fn with_batch_size(mut self, size: Option<usize>) -> Self {
+        assert!(size != Some(0), "batch size must be > 0");
+        self.batch_size = size;
+        self
+    }
+
     /// One PA epoch. For each sample in shuffled order:
     ///
     ///   loss  = max(0, 1 - similarity(w_true, x) + similarity(w_rival, x))
@@ -97,39 +213,14 @@ where
     ///       w_rival ← w_rival − τ · x
     ///
     /// Passive on correct margin-satisfying predictions — w is unchanged.
+    /// Online, unless a batch size is set.
     pub fn step(&mut self, epoch: usize) -> EpochResult {
         self.indices.shuffle(&mut self.rng);
 
-        // TODO - batch training like PerceptronTrainer - allows parallel iteration
-        let errors = self
-            .indices
-            .iter()
-            .filter(|&&idx| {
-                let hdv = &self.samples[idx];
-                let true_class = self.labels[idx].into();
-                let (predicted, _) = nearest(hdv, &self.prototypes);
-
-                // Multiclass hinge loss: margin of true class vs best rival.
-                // With cosine similarity this is bounded in [-2, 0] when violated.
-                // Loss > 0 iff the margin constraint is not satisfied.
-                let sim_true = hdv.similarity(&self.prototypes[true_class]);
-                let sim_rival = hdv.similarity(&self.prototypes[predicted]);
-                let loss = (1.0 - sim_true + sim_rival).max(0.0);
-
-                if loss > 0.0 {
-                    let norm_sq = 1.0; // true for binary, bipolar and normalised real/complex vectors ...
-                    let tau = self.variant.tau(loss.into(), norm_sq);
-
-                    self.accumulators[true_class].add(hdv, tau);
-                    self.accumulators[predicted].add(hdv, -tau);
-
-                    self.prototypes[true_class] = self.accumulators[true_class].finalize();
-                    self.prototypes[predicted] = self.accumulators[predicted].finalize();
-                }
-
-                true_class != predicted
-            })
-            .count();
+        let errors = match self.batch_size {
+            None => self.step_online(),
+            Some(size) => self.step_batch(size),
+        };
 
         EpochResult {
             epoch,
@@ -138,7 +229,65 @@ where
         }
     }
 
-    pub fn fit(mut self, max_epochs: usize) -> (PrototypeModel<T, N>, Vec<EpochResult>) {
+    fn step_online(&mut self) -> usize {
+        let mut errors = 0;
+        for i in 0..self.indices.len() {
+            let idx = self.indices[i];
+            let hdv = &self.samples[idx];
+            let true_class = self.labels[idx].into();
+            let (predicted, tau) = pa_loss(hdv, true_class, &self.prototypes, self.variant);
+            if tau > 0.0 {
+                self.accumulators[true_class].add(hdv, tau);
+                self.accumulators[predicted].add(hdv, -tau);
+
+                self.prototypes[true_class] = self.accumulators[true_class].finalize();
+                self.prototypes[predicted] = self.accumulators[predicted].finalize();
+            }
+            if true_class != predicted {
+                errors += 1;
+            }
+        }
+        errors
+    }
+
+    fn step_batch(&mut self, size: usize) -> usize {
+        let (samples, labels, variant) = (self.samples, self.labels, self.variant);
+        let mut errors = 0;
+        for b in (0..self.indices.len()).step_by(size) {
+            let batch = &self.indices[b..(b + size).min(self.indices.len())];
+            let losses: Vec<(usize, usize, usize, f64)> = batch
+                .par_iter()
+                .map(|&idx| {
+                    let true_class = labels[idx].into();
+                    let (predicted, tau) =
+                        pa_loss(&samples[idx], true_class, &self.prototypes, variant);
+                    (idx, true_class, predicted, tau)
+                })
+                .collect();
+
+            // a correct prediction is its own rival - + τ·x and − τ·x cancel
+            let updates: Vec<(usize, usize, f64)> = losses
+                .iter()
+                .filter(|&&(_, true_class, predicted, tau)| tau > 0.0 && true_class != predicted)
+                .flat_map(|&(idx, true_class, predicted, tau)| {
+                    [(idx, true_class, tau), (idx, predicted, -tau)]
+                })
+                .collect();
+            errors += updates.len() / 2;
+
+            if !updates.is_empty() {
+                par_accumulate(&mut self.accumulators, samples, &updates);
+                self.prototypes = self
+                    .accumulators
+                    .iter_mut()
+                    .map(|acc| acc.finalize())
+                    .collect();
+            }
+        }
+        errors
+    }
+
+    pub fn fit(mut self, max_epochs: usize) -> (DynPrototypeModel<T>, Vec<EpochResult>) {
         let mut history = Vec::with_capacity(max_epochs);
         for epoch in 1..=max_epochs {
             let result = self.step(epoch);
@@ -149,36 +298,126 @@ where
             }
         }
         (
-            PrototypeModel {
+            DynPrototypeModel {
                 prototypes: self.prototypes,
             },
             history,
         )
     }
 
-    pub fn prototypes(&self) -> &[T; N] {
+    pub fn prototypes(&self) -> &[T] {
         &self.prototypes
     }
 
-    pub fn into_model(self) -> PrototypeModel<T, N> {
-        PrototypeModel {
+    /// Scores the current prototypes on the training samples
+    pub fn score(&self, epoch: usize) -> EpochResult {
+        let (samples, labels, prototypes) = (self.samples, self.labels, &self.prototypes);
+        let errors = self
+            .indices
+            .par_iter()
+            .filter(|&&idx| nearest(&samples[idx], prototypes).0 != labels[idx].into())
+            .count();
+        EpochResult {
+            epoch,
+            correct: self.indices.len() - errors,
+            errors,
+        }
+    }
+
+    pub fn model(&self) -> DynPrototypeModel<T> {
+        DynPrototypeModel {
+            prototypes: self.prototypes.clone(),
+        }
+    }
+
+    pub fn into_model(self) -> DynPrototypeModel<T> {
+        DynPrototypeModel {
             prototypes: self.prototypes,
         }
     }
 }
 
-impl<'a, T, L, R, const N: usize> Trainer<T> for PaTrainer<'a, T, L, R, N>
+impl<'a, T, L, R> Trainer<T> for DynPaTrainer<'a, T, L, R>
 where
     T: HyperVector + Send + Sync,
     L: Into<usize> + Copy + Send + Sync,
     R: Rng,
 {
-    type Model = PrototypeModel<T, N>;
+    type Model = DynPrototypeModel<T>;
     fn step(&mut self, epoch: usize) -> EpochResult {
         self.step(epoch)
     }
 
+    fn score(&self, epoch: usize) -> EpochResult {
+        self.score(epoch)
+    }
+
+    fn model(&self) -> Self::Model {
+        self.model()
+    }
+
     fn into_model(self) -> Self::Model {
         self.into_model()
     }
 }
+
+// (rival, τ) of a sample - the rival is the nearest prototype, τ is 0 when the margin
+// is satisfied
+fn pa_loss<T: HyperVector>(
+    hdv: &T,
+    true_class: usize,
+    prototypes: &[T],
+    variant: PaVariant,
+) -> (usize, f64) {
+    let (predicted, _) = nearest(hdv, prototypes);
+
+    // Multiclass hinge loss: margin of true class vs best rival.
+    // With cosine similarity this is bounded in [-2, 0] when violated.
+    // Loss > 0 iff the margin constraint is not satisfied.
+    let sim_true = hdv.similarity(&prototypes[true_class]);
+    let sim_rival = hdv.similarity(&prototypes[predicted]);
+    let loss = (1.0 - sim_true + sim_rival).max(0.0);
+
+    if loss > 0.0 {
+        let norm_sq = 1.0; // true for binary, bipolar and normalised real/complex vectors ...
+        (predicted, variant.tau(loss.into(), norm_sq))
+    } else {
+        (predicted, 0.0)
+    }
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod pa_tests {
+    use super::*;
+    use crate::trainer::Classifier;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_pa_batch() {
+        let mut mt = MersenneTwister64::new(11);
+        let centers: Vec<Binary<16>> = (0..4).map(|_| Binary::random(&mut mt)).collect();
+        let labels: Vec<usize> = (0..200).map(|i| i % 4).collect();
+        let samples: Vec<Binary<16>> = labels
+            .iter()
+            .map(|&l| centers[l].flip(300, &mut mt))
+            .collect();
+
+        for batch_size in [None, Some(1), Some(16), Some(200)] {
+            let trainer = DynPaTrainer::new(
+                &samples,
+                &labels,
+                None,
+                4,
+                PaVariant::PaI { c: 0.1 },
+                MersenneTwister64::new(1),
+            )
+            .with_batch_size(batch_size);
+            let (model, history) = trainer.fit(10);
+            assert_eq!(history.last().unwrap().errors, 0);
+            assert_eq!(model.accuracy(&samples, &labels).0, samples.len());
+        }
+    }
+}
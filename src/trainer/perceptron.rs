@@ -1,4 +1,7 @@
-use crate::trainer::{EpochResult, PrototypeModel, Trainer};
+use crate::trainer::{
+    DynPrototypeModel, EpochResult, PrototypeModel, Schedule, Trainer, class_accumulators,
+    par_accumulate,
+};
 use crate::{Accumulator, HyperVector, nearest};
 use rand::Rng;
 use rand::prelude::SliceRandom;
@@ -11,18 +14,15 @@ use rayon::prelude::*;
 /// - `R`: RNG (used for per-epoch shuffling)
 /// - `L`: Label type — must be convertible to `usize` as a class index
 /// - `N`: Number of classes (const generic)
+///
+/// See DynPerceptronTrainer when the number of classes is known only at runtime.
 pub struct PerceptronTrainer<'a, T, L, R, const N: usize>
 where
     T: HyperVector + Send + Sync,
     L: Into<usize> + Copy + Send + Sync,
     R: Rng,
 {
-    accumulators: [T::Accumulator; N],
-    prototypes: [T; N],
-    samples: &'a [T],
-    labels: &'a [L],
-    indices: Vec<usize>,
-    rng: R,
+    inner: DynPerceptronTrainer<'a, T, L, R>,
 }
 
 impl<'a, T, L, R, const N: usize> PerceptronTrainer<'a, T, L, R, N>
@@ -32,15 +32,110 @@ where
     R: Rng,
 {
     pub fn new(samples: &'a [T], labels: &'a [L], exclude: Option<usize>, rng: R) -> Self {
-        assert_eq!(samples.len(), labels.len());
+        Self {
+            inner: DynPerceptronTrainer::new(samples, labels, exclude, N, rng),
+        }
+    }
+
+    /// Learning rate schedule, default lr 1 / sqrt(epoch)
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.inner.schedule = schedule;
+        self
+    }
+
+    pub fn step(&mut self, epoch: usize) -> EpochResult {
+        self.inner.step(epoch)
+    }
 
-        let mut accumulators: [T::Accumulator; N] = core::array::from_fn(|_| T::Accumulator::new());
+    pub fn score(&self, epoch: usize) -> EpochResult {
+        self.inner.score(epoch)
+    }
 
-        for (hdv, label) in samples.iter().zip(labels.iter()) {
-            accumulators[(*label).into()].add(hdv, 1.0);
+    pub fn fit(mut self, max_epochs: usize) -> (PrototypeModel<T, N>, Vec<EpochResult>) {
+        let mut history = Vec::with_capacity(max_epochs);
+        for epoch in 1..=max_epochs {
+            let result = self.step(epoch);
+            history.push(result);
+            if result.errors == 0 {
+                break;
+            }
         }
+        (self.into_model(), history)
+    }
+
+    pub fn model(&self) -> PrototypeModel<T, N> {
+        let Ok(prototypes) = self.inner.prototypes.clone().try_into() else {
+            unreachable!()
+        };
+        PrototypeModel { prototypes }
+    }
+
+    pub fn into_model(self) -> PrototypeModel<T, N> {
+        let Ok(prototypes) = self.inner.prototypes.try_into() else {
+            unreachable!()
+        };
+        PrototypeModel { prototypes }
+    }
+}
+
+impl<'a, T, L, R, const N: usize> Trainer<T> for PerceptronTrainer<'a, T, L, R, N>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    type Model = PrototypeModel<T, N>;
+
+    fn step(&mut self, epoch: usize) -> EpochResult {
+        self.step(epoch)
+    }
+
+    fn score(&self, epoch: usize) -> EpochResult {
+        self.score(epoch)
+    }
+
+    fn model(&self) -> Self::Model {
+        self.model()
+    }
 
-        let prototypes: [T; N] = core::array::from_fn(|i| accumulators[i].finalize());
+    fn into_model(self) -> Self::Model {
+        self.into_model()
+    }
+}
+
+/// PerceptronTrainer with the number of classes given at runtime
+pub struct DynPerceptronTrainer<'a, T, L, R>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    accumulators: Vec<T::Accumulator>,
+    prototypes: Vec<T>,
+    samples: &'a [T],
+    labels: &'a [L],
+    indices: Vec<usize>,
+    rng: R,
+    schedule: Schedule,
+}
+
+impl<'a, T, L, R> DynPerceptronTrainer<'a, T, L, R>
+where
+    T: HyperVector + Send + Sync,
+    L: Into<usize> + Copy + Send + Sync,
+    R: Rng,
+{
+    pub fn new(
+        samples: &'a [T],
+        labels: &'a [L],
+        exclude: Option<usize>,
+        n_classes: usize,
+        rng: R,
+    ) -> Self {
+        assert_eq!(samples.len(), labels.len());
+
+        let mut accumulators = class_accumulators(samples, labels, exclude, n_classes);
+        let prototypes: Vec<T> = accumulators.iter_mut().map(|acc| acc.finalize()).collect();
         //let indices = (0..samples.len()).collect();
         let indices = (0..samples.len()).filter(|i| Some(*i) != exclude).collect();
 
@@ -51,12 +146,19 @@ where
             labels,
             indices,
             rng,
+            schedule: Schedule::default(),
         }
     }
 
+    /// Learning rate schedule, default lr 1 / sqrt(epoch)
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
     pub fn step(&mut self, epoch: usize) -> EpochResult {
         self.indices.shuffle(&mut self.rng);
-        let lr = 1.0 / (epoch as f64).sqrt();
+        let lr = self.schedule.rate(epoch);
 
         let errors: Vec<(usize, usize, usize)> = self
             .indices
@@ -75,13 +177,19 @@ where
 
         let error_count = errors.len();
 
-        for (idx, true_class, predicted) in errors {
-            let hdv = &self.samples[idx];
-            self.accumulators[true_class].add(hdv, lr);
-            self.accumulators[predicted].add(hdv, -lr);
-        }
+        let updates: Vec<(usize, usize, f64)> = errors
+            .iter()
+            .flat_map(|&(idx, true_class, predicted)| {
+                [(idx, true_class, lr), (idx, predicted, -lr)]
+            })
+            .collect();
+        par_accumulate(&mut self.accumulators, self.samples, &updates);
 
-        self.prototypes = core::array::from_fn(|i| self.accumulators[i].finalize());
+        self.prototypes = self
+            .accumulators
+            .iter_mut()
+            .map(|acc| acc.finalize())
+            .collect();
 
         EpochResult {
             epoch,
@@ -90,7 +198,7 @@ where
         }
     }
 
-    pub fn fit(mut self, max_epochs: usize) -> (PrototypeModel<T, N>, Vec<EpochResult>) {
+    pub fn fit(mut self, max_epochs: usize) -> (DynPrototypeModel<T>, Vec<EpochResult>) {
         let mut history = Vec::with_capacity(max_epochs);
         for epoch in 1..=max_epochs {
             let result = self.step(epoch);
@@ -102,25 +210,54 @@ where
         (self.into_model(), history)
     }
 
-    pub fn into_model(self) -> PrototypeModel<T, N> {
-        PrototypeModel {
+    /// Scores the current prototypes on the training samples
+    pub fn score(&self, epoch: usize) -> EpochResult {
+        let (samples, labels, prototypes) = (self.samples, self.labels, &self.prototypes);
+        let errors = self
+            .indices
+            .par_iter()
+            .filter(|&&idx| nearest(&samples[idx], prototypes).0 != labels[idx].into())
+            .count();
+        EpochResult {
+            epoch,
+            correct: self.indices.len() - errors,
+            errors,
+        }
+    }
+
+    pub fn model(&self) -> DynPrototypeModel<T> {
+        DynPrototypeModel {
+            prototypes: self.prototypes.clone(),
+        }
+    }
+
+    pub fn into_model(self) -> DynPrototypeModel<T> {
+        DynPrototypeModel {
             prototypes: self.prototypes,
         }
     }
 }
 
-impl<'a, T, L, R, const N: usize> Trainer<T> for PerceptronTrainer<'a, T, L, R, N>
+impl<'a, T, L, R> Trainer<T> for DynPerceptronTrainer<'a, T, L, R>
 where
     T: HyperVector + Send + Sync,
     L: Into<usize> + Copy + Send + Sync,
     R: Rng,
 {
-    type Model = PrototypeModel<T, N>;
+    type Model = DynPrototypeModel<T>;
 
     fn step(&mut self, epoch: usize) -> EpochResult {
         self.step(epoch)
     }
 
+    fn score(&self, epoch: usize) -> EpochResult {
+        self.score(epoch)
+    }
+
+    fn model(&self) -> Self::Model {
+        self.model()
+    }
+
     fn into_model(self) -> Self::Model {
         self.into_model()
     }
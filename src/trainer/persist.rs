@@ -0,0 +1,317 @@
+use crate::HyperVector;
+use crate::trainer::{DynPrototypeModel, EpochResult, MultiPrototypeModel, PrototypeModel};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// ── Model files ───────────────────────────────────────────────────────────────
+//
+// A trained model together with what is needed to use it in another process:
+//
+//   magic "HDVMODEL", format version       u32
+//   hypervector type tag, DIM              HyperVector::TAG, checked on load
+//   n_classes, proto_per_class
+//   ModelInfo                              class names, encoder, seed, metadata, history
+//   prototypes                             HyperVector::write, n_classes * proto_per_class
+//
+// Integers are little endian u64, strings are length prefixed UTF-8. The prototypes
+// use the hypervector's own format, little endian as well.
+
+const MAGIC: &[u8; 8] = b"HDVMODEL";
+const VERSION: u32 = 2;
+
+/// Everything about a model except its prototypes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelInfo {
+    /// One name per class, in class index order - may be empty
+    pub class_names: Vec<String>,
+    /// Encoder configuration, e.g. "fwht" - whatever is needed to rebuild it
+    pub encoder: String,
+    /// Seed of the rng the encoder was generated from
+    pub seed: u64,
+    /// Free-form key/value pairs - trainer, hyper parameters, data set, ...
+    pub metadata: Vec<(String, String)>,
+    pub history: Vec<EpochResult>,
+}
+
+impl ModelInfo {
+    pub fn new(encoder: &str, seed: u64) -> Self {
+        Self {
+            encoder: encoder.to_string(),
+            seed,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_class_names<S: AsRef<str>>(mut self, names: &[S]) -> Self {
+        self.class_names = names.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: impl ToString) -> Self {
+        self.metadata.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_history(mut self, history: &[EpochResult]) -> Self {
+        self.history = history.to_vec();
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Models that can be written to a model file and read back
+pub trait Persist: Sized {
+    fn save(&self, path: impl AsRef<Path>, info: &ModelInfo) -> io::Result<()>;
+    fn load(path: impl AsRef<Path>) -> io::Result<(Self, ModelInfo)>;
+}
+
+impl<T: HyperVector, const N: usize> Persist for PrototypeModel<T, N> {
+    fn save(&self, path: impl AsRef<Path>, info: &ModelInfo) -> io::Result<()> {
+        write_model(path.as_ref(), &self.prototypes, N, 1, info)
+    }
+
+    fn load(path: impl AsRef<Path>) -> io::Result<(Self, ModelInfo)> {
+        let (prototypes, n_classes, proto_per_class, info) = read_model::<T>(path.as_ref())?;
+        if n_classes != N || proto_per_class != 1 {
+            return Err(invalid(format!(
+                "expected {N} classes with 1 prototype, got {n_classes} x {proto_per_class}"
+            )));
+        }
+        let Ok(prototypes) = prototypes.try_into() else {
+            unreachable!()
+        };
+        Ok((Self { prototypes }, info))
+    }
+}
+
+impl<T: HyperVector> Persist for DynPrototypeModel<T> {
+    fn save(&self, path: impl AsRef<Path>, info: &ModelInfo) -> io::Result<()> {
+        write_model(
+            path.as_ref(),
+            &self.prototypes,
+            self.prototypes.len(),
+            1,
+            info,
+        )
+    }
+
+    fn load(path: impl AsRef<Path>) -> io::Result<(Self, ModelInfo)> {
+        let (prototypes, n_classes, proto_per_class, info) = read_model::<T>(path.as_ref())?;
+        if proto_per_class != 1 {
+            return Err(invalid(format!(
+                "expected 1 prototype per class, got {n_classes} x {proto_per_class}"
+            )));
+        }
+        Ok((Self { prototypes }, info))
+    }
+}
+
+/// A single prototype model file loads as a MultiPrototypeModel with one prototype per class
+impl<T: HyperVector> Persist for MultiPrototypeModel<T> {
+    fn save(&self, path: impl AsRef<Path>, info: &ModelInfo) -> io::Result<()> {
+        write_model(
+            path.as_ref(),
+            &self.prototypes,
+            self.n_classes,
+            self.proto_per_class,
+            info,
+        )
+    }
+
+    /// proto_labels is rebuilt from the layout - the class of each prototype
+    fn load(path: impl AsRef<Path>) -> io::Result<(Self, ModelInfo)> {
+        let (prototypes, n_classes, proto_per_class, info) = read_model::<T>(path.as_ref())?;
+        Ok((Self::new(prototypes, n_classes, proto_per_class), info))
+    }
+}
+
+fn write_model<T: HyperVector>(
+    path: &Path,
+    prototypes: &[T],
+    n_classes: usize,
+    proto_per_class: usize,
+    info: &ModelInfo,
+) -> io::Result<()> {
+    assert_eq!(prototypes.len(), n_classes * proto_per_class);
+    let mut file = BufWriter::new(File::create(path)?);
+
+    // header
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    put_str(&mut buf, T::TAG);
+    put_u64(&mut buf, T::DIM as u64);
+    put_u64(&mut buf, n_classes as u64);
+    put_u64(&mut buf, proto_per_class as u64);
+
+    // info
+    put_u64(&mut buf, info.class_names.len() as u64);
+    for name in &info.class_names {
+        put_str(&mut buf, name);
+    }
+    put_str(&mut buf, &info.encoder);
+    put_u64(&mut buf, info.seed);
+    put_u64(&mut buf, info.metadata.len() as u64);
+    for (key, value) in &info.metadata {
+        put_str(&mut buf, key);
+        put_str(&mut buf, value);
+    }
+    put_u64(&mut buf, info.history.len() as u64);
+    for r in &info.history {
+        put_u64(&mut buf, r.epoch as u64);
+        put_u64(&mut buf, r.correct as u64);
+        put_u64(&mut buf, r.errors as u64);
+    }
+    file.write_all(&buf)?;
+
+    for p in prototypes {
+        p.write(&mut file)?;
+    }
+    file.flush()
+}
+
+type ModelParts<T> = (Vec<T>, usize, usize, ModelInfo);
+
+fn read_model<T: HyperVector>(path: &Path) -> io::Result<ModelParts<T>> {
+    let mut file = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 8];
+    file.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid(format!("{}: not a model file", path.display())));
+    }
+    let mut version = [0u8; 4];
+    file.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != VERSION {
+        return Err(invalid(format!(
+            "unsupported model format version {version}"
+        )));
+    }
+    let tag = get_str(&mut file)?;
+    let dim = get_usize(&mut file)?;
+    if tag != T::TAG || dim != T::DIM {
+        return Err(invalid(format!(
+            "model has {tag} ({dim} dims), expected {} ({} dims)",
+            T::TAG,
+            T::DIM
+        )));
+    }
+    let n_classes = get_usize(&mut file)?;
+    let proto_per_class = get_usize(&mut file)?;
+    if n_classes == 0 || proto_per_class == 0 {
+        return Err(invalid("model without prototypes".to_string()));
+    }
+
+    let mut info = ModelInfo::default();
+    for _ in 0..get_usize(&mut file)? {
+        info.class_names.push(get_str(&mut file)?);
+    }
+    info.encoder = get_str(&mut file)?;
+    info.seed = get_u64(&mut file)?;
+    for _ in 0..get_usize(&mut file)? {
+        let key = get_str(&mut file)?;
+        let value = get_str(&mut file)?;
+        info.metadata.push((key, value));
+    }
+    for _ in 0..get_usize(&mut file)? {
+        info.history.push(EpochResult {
+            epoch: get_usize(&mut file)?,
+            correct: get_usize(&mut file)?,
+            errors: get_usize(&mut file)?,
+        });
+    }
+
+    let prototypes = (0..n_classes * proto_per_class)
+        .map(|_| T::read(&mut file))
+        .collect::<io::Result<Vec<T>>>()?;
+    Ok((prototypes, n_classes, proto_per_class, info))
+}
+
+fn put_u64(buf: &mut Vec<u8>, v: u64) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    put_u64(buf, s.len() as u64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn get_u64(file: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    file.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn get_usize(file: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(get_u64(file)?).map_err(|e| invalid(e.to_string()))
+}
+
+fn get_str(file: &mut impl Read) -> io::Result<String> {
+    let len = get_usize(file)?;
+    let mut buf = Vec::new();
+    file.by_ref().take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    String::from_utf8(buf).map_err(|e| invalid(e.to_string()))
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod persist_tests {
+    use super::*;
+    use crate::trainer::Classifier;
+    use crate::types::binary::{Binary, Bipolar};
+    use crate::types::real::RealHDV;
+    use mersenne_twister_rs::MersenneTwister64;
+
+    #[test]
+    fn test_save_load() {
+        let mut mt = MersenneTwister64::new(42);
+        let model = PrototypeModel::<Binary<4>, 3> {
+            prototypes: std::array::from_fn(|_| Binary::random(&mut mt)),
+        };
+        let info = ModelInfo::new("fwht", 7)
+            .with_class_names(&["a", "b", "c"])
+            .with_metadata("trainer", "perceptron")
+            .with_history(&[EpochResult {
+                epoch: 1,
+                correct: 9,
+                errors: 1,
+            }]);
+        let path = std::env::temp_dir().join(format!("persist_test_{}.hdv", std::process::id()));
+        model.save(&path, &info).unwrap();
+
+        let (loaded, loaded_info) = PrototypeModel::<Binary<4>, 3>::load(&path).unwrap();
+        assert_eq!(loaded.prototypes, model.prototypes);
+        assert_eq!(loaded_info, info);
+        assert_eq!(loaded_info.get("trainer"), Some("perceptron"));
+        let query = model.prototypes[2].flip(10, &mut mt);
+        assert_eq!(loaded.predict(&query), 2);
+
+        // the layout and the type are checked
+        assert!(PrototypeModel::<Binary<4>, 2>::load(&path).is_err());
+        assert!(PrototypeModel::<RealHDV<256>, 3>::load(&path).is_err());
+        // same dimension, different type
+        assert!(PrototypeModel::<Bipolar<4>, 3>::load(&path).is_err());
+        let (dynamic, _) = DynPrototypeModel::<Binary<4>>::load(&path).unwrap();
+        assert_eq!(dynamic.prototypes, model.prototypes);
+        let (multi, _) = MultiPrototypeModel::<Binary<4>>::load(&path).unwrap();
+        assert_eq!((multi.n_classes, multi.proto_per_class), (3, 1));
+        assert_eq!(multi.predict(&query), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use crate::metrics::RegressionMetrics;
+use crate::trainer::{Schedule, kmeans::KMeans};
+use crate::{HyperVector, nearest};
+use rand::Rng;
+use rand::prelude::SliceRandom;
+use rayon::prelude::*;
+
+// ── Regression ────────────────────────────────────────────────────────────────
+//
+// RegHD style regression: a model vector with a linear readout - the predicted
+// value is bias + <model, features of the sample>, the features being the
+// unpacked components of the hypervector, centered on the training mean. For
+// Binary these are the bits, for Modular the phases.
+//
+// Several models split the input space: the samples are clustered with k-means and
+// every cluster has a model of its own, predicting for the samples nearest to it.
+// The models are trained online on squared error with normalized LMS steps.
+
+// k-means restarts when clustering the samples for several models
+const N_INIT: usize = 3;
+
+// Relative decrease of the training RMSE below which fit stops
+const TOLERANCE: f64 = 1e-4;
+
+pub trait Regressor<T: HyperVector> {
+    fn predict(&self, h: &T) -> f32;
+
+    fn predict_all(&self, samples: &[T]) -> Vec<f32>
+    where
+        T: Send + Sync,
+        Self: Sync,
+    {
+        samples.par_iter().map(|h| self.predict(h)).collect()
+    }
+
+    /// MAE, RMSE and R² of the predictions
+    fn evaluate(&self, samples: &[T], targets: &[f32]) -> RegressionMetrics
+    where
+        T: Send + Sync,
+        Self: Sync,
+    {
+        RegressionMetrics::new(&self.predict_all(samples), targets)
+    }
+}
+
+/// Model vectors with a linear readout, see RegHdTrainer
+#[derive(Debug, Clone)]
+pub struct RegHdModel<T: HyperVector> {
+    /// Cluster centers, one per model - the nearest one's model predicts
+    pub clusters: Vec<T>,
+    /// Readout weights of every model, one per component of T::unpack
+    pub weights: Vec<Vec<f32>>,
+    /// Mean features of the training samples, subtracted before the readout
+    pub center: Vec<f32>,
+    /// Mean target of the training samples
+    pub bias: f32,
+}
+
+impl<T: HyperVector> RegHdModel<T> {
+    pub fn n_models(&self) -> usize {
+        self.weights.len()
+    }
+
+    // the centered features of h
+    fn features(&self, h: &T) -> Vec<f32> {
+        let mut f = h.unpack();
+        for (x, c) in f.iter_mut().zip(&self.center) {
+            *x -= c;
+        }
+        f
+    }
+
+    fn model_of(&self, h: &T) -> usize {
+        if self.clusters.len() > 1 {
+            nearest(h, &self.clusters).0
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: HyperVector> Regressor<T> for RegHdModel<T> {
+    fn predict(&self, h: &T) -> f32 {
+        self.bias + dot(&self.weights[self.model_of(h)], &self.features(h))
+    }
+}
+
+/// Trains RegHdModel on squared error, in random order every epoch. A sample moves
+/// the readout of its model by lr * error along its features, normalized by their
+/// squared length - with lr 1 the model fits the sample exactly.
+pub struct RegHdTrainer<'a, T: HyperVector, R: Rng> {
+    model: RegHdModel<T>,
+    samples: &'a [T],
+    targets: &'a [f32],
+    // model of every sample
+    assignments: Vec<usize>,
+    indices: Vec<usize>,
+    rng: R,
+    schedule: Schedule,
+}
+
+impl<'a, T, R> RegHdTrainer<'a, T, R>
+where
+    T: HyperVector + Send + Sync,
+    R: Rng,
+{
+    pub fn new(samples: &'a [T], targets: &'a [f32], n_models: usize, mut rng: R) -> Self {
+        assert!(!samples.is_empty() && samples.len() == targets.len());
+        assert!(n_models > 0, "n_models must be > 0");
+
+        let clusters = if n_models > 1 {
+            KMeans::fit(samples, n_models, N_INIT, 100, &mut rng).centroids
+        } else {
+            Vec::new()
+        };
+        let n = samples.len() as f32;
+        let center = samples
+            .par_iter()
+            .map(|h| h.unpack())
+            .reduce_with(|mut a, b| {
+                a.iter_mut().zip(&b).for_each(|(x, y)| *x += y);
+                a
+            })
+            .unwrap()
+            .into_iter()
+            .map(|x| x / n)
+            .collect::<Vec<f32>>();
+        let model = RegHdModel {
+            clusters,
+            weights: vec![vec![0.0; center.len()]; n_models],
+            center,
+            bias: targets.iter().sum::<f32>() / n,
+        };
+        let assignments = samples.par_iter().map(|h| model.model_of(h)).collect();
+
+        Self {
+            model,
+            samples,
+            targets,
+            assignments,
+            indices: (0..samples.len()).collect(),
+            rng,
+            schedule: Schedule::default(),
+        }
+    }
+
+    /// Learning rate schedule, default lr 1 / sqrt(epoch)
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// One pass over the samples. Returns the metrics on the training set after it.
+    pub fn step(&mut self, epoch: usize) -> RegressionMetrics {
+        let lr = self.schedule.rate(epoch) as f32;
+        self.indices.shuffle(&mut self.rng);
+        for &i in &self.indices {
+            let features = self.model.features(&self.samples[i]);
+            let norm = dot(&features, &features);
+            if norm == 0.0 {
+                continue;
+            }
+            let weights = &mut self.model.weights[self.assignments[i]];
+            let error = self.targets[i] - self.model.bias - dot(weights, &features);
+            let step = lr * error / norm;
+            for (w, x) in weights.iter_mut().zip(&features) {
+                *w += step * x;
+            }
+        }
+        self.model.evaluate(self.samples, self.targets)
+    }
+
+    /// Trains until max_epochs, or until the training RMSE decreases by less than
+    /// 1e-4 of itself. Returns the model and the training metrics of every epoch.
+    pub fn fit(mut self, max_epochs: usize) -> (RegHdModel<T>, Vec<RegressionMetrics>) {
+        let mut history: Vec<RegressionMetrics> = Vec::with_capacity(max_epochs);
+        for epoch in 1..=max_epochs {
+            let result = self.step(epoch);
+            let converged = history
+                .last()
+                .is_some_and(|last| last.rmse - result.rmse <= TOLERANCE * last.rmse);
+            history.push(result);
+            if converged {
+                break;
+            }
+        }
+        (self.into_model(), history)
+    }
+
+    pub fn model(&self) -> RegHdModel<T> {
+        self.model.clone()
+    }
+
+    pub fn into_model(self) -> RegHdModel<T> {
+        self.model
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// -- Tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod regression_tests {
+    use super::*;
+    use crate::encoding::ScalarEncoder;
+    use crate::types::binary::Binary;
+    use mersenne_twister_rs::MersenneTwister64;
+    use rand::RngExt;
+
+    #[test]
+    fn test_reghd() {
+        let mut mt = MersenneTwister64::new(12);
+        let encoder = ScalarEncoder::<Binary<16>>::new(0.0, 1.0, 32, &mut mt);
+        let xs: Vec<f32> = (0..300).map(|_| mt.random::<f32>()).collect();
+        let samples: Vec<Binary<16>> = xs.iter().map(|&x| encoder.encode(x).clone()).collect();
+        // a smooth target of the level encoded value
+        let targets: Vec<f32> = xs.iter().map(|&x| 3.0 + 2.0 * (6.0 * x).sin()).collect();
+        let (train, test) = samples.split_at(200);
+        let (train_targets, test_targets) = targets.split_at(200);
+
+        let (model, history) = RegHdTrainer::new(train, train_targets, 1, &mut mt)
+            .with_schedule(Schedule::Constant(0.5))
+            .fit(100);
+        assert_eq!(model.n_models(), 1);
+        assert!(history.len() > 1 && history.len() <= 100);
+        assert!(history.last().unwrap().rmse < history[0].rmse);
+        let single = model.evaluate(test, test_targets);
+        assert!(single.rmse < 0.3 && single.mae <= single.rmse, "{single}");
+        assert!(single.r2 > 0.9);
+
+        let (model, _) = RegHdTrainer::new(train, train_targets, 4, &mut mt)
+            .with_schedule(Schedule::Constant(0.5))
+            .fit(100);
+        assert_eq!(model.clusters.len(), 4);
+        let multi = model.evaluate(test, test_targets);
+        assert!(multi.rmse < 0.3, "{multi}");
+    }
+}
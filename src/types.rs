@@ -63,6 +63,7 @@ mod tests {
         modular::Modular,
         real::RealHDV,
     };
+    use mersenne_twister_rs::MersenneTwister64;
 
     fn test_permute_unpermute<T: HyperVector + std::fmt::Debug + std::cmp::PartialEq>() {
         //let mut rng = MersenneTwister64::new(42);
@@ -125,4 +126,95 @@ mod tests {
     fn test_complex_bind_unbind() {
         test_bind_unbind::<ComplexHDV<1000>>(0.5);
     }
+
+    // merging two partial sums gives the sum of all - exact up to float rounding
+    fn test_merge<T: HyperVector>() {
+        use crate::types::traits::{Accumulator, UnitAccumulator};
+        let mut rng = MersenneTwister64::new(42);
+        let vs: Vec<T> = (0..7).map(|_| T::random(&mut rng)).collect();
+
+        let mut all = T::Accumulator::default();
+        let mut first = T::Accumulator::default();
+        let mut second = T::Accumulator::default();
+        let mut unit_all = T::UnitAccumulator::default();
+        let mut unit_first = T::UnitAccumulator::default();
+        let mut unit_second = T::UnitAccumulator::default();
+        for (i, v) in vs.iter().enumerate() {
+            let weight = 0.5 + i as f64;
+            all.add(v, weight);
+            unit_all.add(v);
+            if i < 3 {
+                first.add(v, weight);
+                unit_first.add(v);
+            } else {
+                second.add(v, weight);
+                unit_second.add(v);
+            }
+        }
+        first.merge(&second);
+        unit_first.merge(&unit_second);
+        assert_eq!(first.count(), all.count());
+        assert_eq!(unit_first.count(), 7);
+        assert!(first.finalize().distance(&all.finalize()) < 1e-5);
+        assert!(unit_first.finalize().distance(&unit_all.finalize()) < 1e-5);
+    }
+
+    // sliding window: subtracting the oldest vector, and exponential decay
+    fn test_subtract_scale<T: HyperVector>()
+    where
+        T::Accumulator: Clone,
+        T::UnitAccumulator: Clone,
+    {
+        use crate::types::traits::{Accumulator, UnitAccumulator};
+        let mut rng = MersenneTwister64::new(42);
+        let vs: Vec<T> = (0..4).map(|_| T::random(&mut rng)).collect();
+
+        let mut window = T::UnitAccumulator::default();
+        let mut fresh = T::UnitAccumulator::default();
+        for v in &vs {
+            window.add(v);
+        }
+        window.subtract(&vs[0]);
+        for v in &vs[1..] {
+            fresh.add(v);
+        }
+        assert_eq!(window.count(), 3);
+        let snapshot = window.snapshot();
+        assert!(snapshot.distance(&fresh.finalize()) < 1e-5);
+        // the snapshot left the accumulator as it was
+        assert!(snapshot.distance(&window.finalize()) < 1e-5);
+
+        let mut decayed = T::Accumulator::default();
+        decayed.add(&vs[0], 1.0);
+        decayed.scale(0.1);
+        decayed.add(&vs[1], 1.0);
+        assert!((decayed.count() - 1.1).abs() < 1e-6);
+        let v = decayed.snapshot();
+        assert!(v.distance(&vs[1]) < v.distance(&vs[0]));
+
+        // a negative factor flips the votes, the count stays the total absolute weight
+        let mut negated = T::Accumulator::default();
+        negated.add(&vs[0], 1.0);
+        negated.scale(-2.0);
+        assert!((negated.count() - 2.0).abs() < 1e-6);
+        assert!(negated.snapshot().distance(&vs[0]) > 0.5);
+    }
+
+    #[test]
+    fn test_subtract_scale_all_types() {
+        test_subtract_scale::<Binary<4>>();
+        test_subtract_scale::<Bipolar<4>>();
+        test_subtract_scale::<RealHDV<256>>();
+        test_subtract_scale::<ComplexHDV<256>>();
+        test_subtract_scale::<Modular<256>>();
+    }
+
+    #[test]
+    fn test_merge_all_types() {
+        test_merge::<Binary<4>>();
+        test_merge::<Bipolar<4>>();
+        test_merge::<RealHDV<256>>();
+        test_merge::<ComplexHDV<256>>();
+        test_merge::<Modular<256>>();
+    }
 }
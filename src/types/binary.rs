@@ -44,6 +44,7 @@ impl<const N: usize, const BIPOLAR: bool> HyperVector for Binary<N, BIPOLAR> {
     type UnitAccumulator = SlicedUnitAcc<N, BIPOLAR, 32>; // 1-64 bit PLANES
     type Element = i8;
     const DIM: usize = N * usize::BITS as usize;
+    const TAG: &'static str = if BIPOLAR { "bipolar" } else { "binary" };
 
     fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let data = std::array::from_fn(|_| rng.next_u64() as usize);
@@ -112,14 +113,15 @@ impl<const N: usize, const BIPOLAR: bool> HyperVector for Binary<N, BIPOLAR> {
             .collect()
     }
 
-    fn write(&self, file: &mut File) -> io::Result<()> {
+    /// Words as u64, so the format does not depend on the platform's usize
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
         for &value in &self.data {
-            file.write_all(&value.to_ne_bytes())?;
+            writer.write_all(&(value as u64).to_le_bytes())?;
         }
         Ok(())
     }
 
-    fn read(file: &mut File) -> io::Result<Self> {
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
         // println!(
         //     "Reading HDV of {} usize elements = {} bits",
         //     N,
@@ -127,9 +129,9 @@ impl<const N: usize, const BIPOLAR: bool> HyperVector for Binary<N, BIPOLAR> {
         // );
         let mut data = [0usize; N];
         for slot in &mut data {
-            let mut buf = [0u8; size_of::<usize>()];
-            file.read_exact(&mut buf)?;
-            *slot = usize::from_ne_bytes(buf);
+            let mut buf = [0u8; size_of::<u64>()];
+            reader.read_exact(&mut buf)?;
+            *slot = u64::from_le_bytes(buf) as usize;
         }
         Ok(Self { data })
     }
@@ -180,14 +182,35 @@ impl<const N: usize, const BIPOLAR: bool> HyperVector for Binary<N, BIPOLAR> {
  * This accumulator uses f32 votes to handle weighted superposition.
  * Maps: 1 -> 1.0, 0 -> -1.0.
  * Finalization uses a tie-breaker to maintain stochastic neutrality.
+ *
+ * By default the binary accumulators break ties with bits hashed from the votes,
+ * so the same inputs always give the same vector while bundles of different
+ * inputs get independent ties. with_seed/with_rng break ties with an rng instead.
  * ========================================================================== */
 
+/// Seed of the hash the default accumulators break ties with
+pub const TIE_SEED: u64 = 0x7e1e_b4ea;
+
+// Tie-breaking bits of word `word` from its vote counts and the accumulator count -
+// the word index is hashed too, so words with the same votes get independent ties
+fn tie_bits(word: usize, counts: impl Iterator<Item = u64>) -> usize {
+    counts.fold(mix(TIE_SEED ^ word as u64), |h, c| mix(h ^ c)) as usize
+}
+
+// splitmix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 #[derive(Debug, Clone)]
 pub struct WeightedAcc<const N: usize, const BIPOLAR: bool, R: Rng = MersenneTwister64> {
     votes: [[f32; usize::BITS as usize]; N], // one vote counter per bit
     //votes: Box<[[f32; usize::BITS as usize]; N]>, // one vote counter per bit
-    count: f64, // total weight added
-    rng: R,
+    count: f64,     // total weight added
+    rng: Option<R>, // None: ties hashed from the votes
 }
 
 impl<const N: usize, const BIPOLAR: bool> Default for WeightedAcc<N, BIPOLAR> {
@@ -202,18 +225,41 @@ impl<const N: usize, const BIPOLAR: bool> WeightedAcc<N, BIPOLAR> {
     }
 }
 
-impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
-    Accumulator<Binary<N, BIPOLAR>> for WeightedAcc<N, BIPOLAR, R>
-{
-    fn new() -> Self {
+impl<const N: usize, const BIPOLAR: bool, R: Rng> WeightedAcc<N, BIPOLAR, R> {
+    /// Ties are broken with `rng`
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_ties(Some(rng))
+    }
+
+    fn with_ties(rng: Option<R>) -> Self {
         Self {
             //votes: Box::new([[0.0; usize::BITS as usize]; N]),
             votes: [[0.0; usize::BITS as usize]; N],
             count: 0.0,
-            rng: R::from_rng(&mut rand::rng()),
+            rng,
         }
     }
 
+    /// Weighted votes per bit - bit j of word i is votes[i][j], positive for 1
+    pub fn votes(&self) -> &[[f32; usize::BITS as usize]; N] {
+        &self.votes
+    }
+
+    pub fn with_seed(seed: u64) -> Self
+    where
+        R: SeedableRng,
+    {
+        Self::with_rng(R::seed_from_u64(seed))
+    }
+}
+
+impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
+    Accumulator<Binary<N, BIPOLAR>> for WeightedAcc<N, BIPOLAR, R>
+{
+    fn new() -> Self {
+        Self::with_ties(None)
+    }
+
     fn add(&mut self, v: &Binary<N, BIPOLAR>, weight: f64) {
         for i in 0..N {
             let word = v.data[i];
@@ -227,10 +273,33 @@ impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
         self.count += weight.abs();
     }
 
+    fn scale(&mut self, factor: f64) {
+        for v in self.votes.iter_mut().flatten() {
+            *v *= factor as f32;
+        }
+        self.count *= factor.abs();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (votes, other) in self.votes.iter_mut().zip(&other.votes) {
+            for (v, o) in votes.iter_mut().zip(other) {
+                *v += o;
+            }
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> Binary<N, BIPOLAR> {
         let data = std::array::from_fn(|i| {
             let mut acc_word = 0usize;
-            let tie_breaker: usize = self.rng.next_u64() as usize;
+            let tie_breaker = match &mut self.rng {
+                Some(rng) => rng.next_u64() as usize,
+                None => tie_bits(
+                    i,
+                    std::iter::once(self.count.to_bits())
+                        .chain(self.votes[i].iter().map(|v| v.to_bits() as u64)),
+                ),
+            };
             for bidx in 0..usize::BITS as usize {
                 // If the consensus is positive, set the bit to 1.
                 // If it's 0.0, we flip a coin to avoid bias.
@@ -256,12 +325,13 @@ impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
  * Variant of WeightedAcc - uses integer votes to handle weighted superposition.
  * ========================================================================== */
 
+#[derive(Clone)]
 pub struct FixPointAcc<const N: usize, const BIPOLAR: bool, R: Rng = MersenneTwister64> {
     // Same as WeightedAcc, but implemented with i32 instead of f32
     //votes: Box<[[i32; usize::BITS as usize]; N]>,
     votes: [[i32; usize::BITS as usize]; N],
     count: f64,
-    rng: R,
+    rng: Option<R>, // None: ties hashed from the votes
 }
 
 impl<const N: usize, const BIPOLAR: bool> Default for FixPointAcc<N, BIPOLAR> {
@@ -276,18 +346,41 @@ impl<const N: usize, const BIPOLAR: bool> FixPointAcc<N, BIPOLAR> {
     }
 }
 
-impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
-    Accumulator<Binary<N, BIPOLAR>> for FixPointAcc<N, BIPOLAR, R>
-{
-    fn new() -> Self {
+impl<const N: usize, const BIPOLAR: bool, R: Rng> FixPointAcc<N, BIPOLAR, R> {
+    /// Ties are broken with `rng`
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_ties(Some(rng))
+    }
+
+    fn with_ties(rng: Option<R>) -> Self {
         Self {
             //votes: Box::new([[0; usize::BITS as usize]; N]),
             votes: [[0; usize::BITS as usize]; N],
             count: 0.0,
-            rng: R::from_rng(&mut rand::rng()),
+            rng,
         }
     }
 
+    /// Scaled integer votes per bit - bit j of word i is votes[i][j], positive for 1
+    pub fn votes(&self) -> &[[i32; usize::BITS as usize]; N] {
+        &self.votes
+    }
+
+    pub fn with_seed(seed: u64) -> Self
+    where
+        R: SeedableRng,
+    {
+        Self::with_rng(R::seed_from_u64(seed))
+    }
+}
+
+impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
+    Accumulator<Binary<N, BIPOLAR>> for FixPointAcc<N, BIPOLAR, R>
+{
+    fn new() -> Self {
+        Self::with_ties(None)
+    }
+
     fn add(&mut self, v: &Binary<N, BIPOLAR>, weight: f64) {
         // Scale factor of 10,000 preserves 4 decimal places
         const SCALE: f64 = 10_000.0;
@@ -308,10 +401,33 @@ impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default>
         self.count += weight.abs();
     }
 
+    fn scale(&mut self, factor: f64) {
+        for v in self.votes.iter_mut().flatten() {
+            *v = (*v as f64 * factor).round() as i32;
+        }
+        self.count *= factor.abs();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (votes, other) in self.votes.iter_mut().zip(&other.votes) {
+            for (v, o) in votes.iter_mut().zip(other) {
+                *v += o;
+            }
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> Binary<N, BIPOLAR> {
         let data = std::array::from_fn(|i| {
             let mut acc_word = 0usize;
-            let tie_breaker: usize = self.rng.next_u64() as usize;
+            let tie_breaker = match &mut self.rng {
+                Some(rng) => rng.next_u64() as usize,
+                None => tie_bits(
+                    i,
+                    std::iter::once(self.count.to_bits())
+                        .chain(self.votes[i].iter().map(|&v| v as u32 as u64)),
+                ),
+            };
             for bidx in 0..usize::BITS as usize {
                 // We only care about the sign of the scaled sum
                 if self.votes[i][bidx] > 0
@@ -342,20 +458,43 @@ type VoteCount = u32;
 #[derive(Debug, Clone)]
 pub struct UnitAcc<const N: usize, const BIPOLAR: bool, R: Rng = MersenneTwister64> {
     votes: [[VoteCount; usize::BITS as usize]; N],
-    count: usize, // total number of vectors added
-    rng: R,
+    count: usize,   // total number of vectors added
+    rng: Option<R>, // None: ties hashed from the votes
 }
 
 impl<const N: usize, const BIPOLAR: bool, R: Rng + SeedableRng + Default> Default
     for UnitAcc<N, BIPOLAR, R>
 {
     fn default() -> Self {
+        Self::with_ties(None)
+    }
+}
+
+impl<const N: usize, const BIPOLAR: bool, R: Rng> UnitAcc<N, BIPOLAR, R> {
+    /// Ties are broken with `rng`
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_ties(Some(rng))
+    }
+
+    fn with_ties(rng: Option<R>) -> Self {
         Self {
             votes: [[0; usize::BITS as usize]; N],
             count: 0,
-            rng: R::from_rng(&mut rand::rng()),
+            rng,
         }
     }
+
+    /// Number of vectors with bit j of word i set, per bit
+    pub fn votes(&self) -> &[[VoteCount; usize::BITS as usize]; N] {
+        &self.votes
+    }
+
+    pub fn with_seed(seed: u64) -> Self
+    where
+        R: SeedableRng,
+    {
+        Self::with_rng(R::seed_from_u64(seed))
+    }
 }
 
 impl<const N: usize, const BIPOLAR: bool> UnitAcc<N, BIPOLAR> {
@@ -382,9 +521,36 @@ impl<const N: usize, const BIPOLAR: bool> UnitAccumulator<Binary<N, BIPOLAR>>
         self.count += 1;
     }
 
+    fn subtract(&mut self, v: &Binary<N, BIPOLAR>) {
+        for i in 0..N {
+            let word = v.data[i];
+            for j in 0..usize::BITS {
+                let flag = ((word >> j) & 1) as VoteCount;
+                self.votes[i][j as usize] -= flag;
+            }
+        }
+        self.count -= 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (votes, other) in self.votes.iter_mut().zip(&other.votes) {
+            for (v, o) in votes.iter_mut().zip(other) {
+                *v += o;
+            }
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> Binary<N, BIPOLAR> {
         let data = std::array::from_fn(|uidx| {
-            let tie_breaker: usize = self.rng.next_u64() as usize;
+            let tie_breaker = match &mut self.rng {
+                Some(rng) => rng.next_u64() as usize,
+                None => tie_bits(
+                    uidx,
+                    std::iter::once(self.count as u64)
+                        .chain(self.votes[uidx].iter().map(|&v| v as u64)),
+                ),
+            };
             let mut acc_word = 0usize;
             for bidx in 0..usize::BITS as usize {
                 let n1 = self.votes[uidx][bidx]; // #1s
@@ -427,6 +593,7 @@ impl<const N: usize, const BIPOLAR: bool> UnitAccumulator<Binary<N, BIPOLAR>>
  * standard integers to calculate the majority threshold.
  * ========================================================================== */
 
+#[derive(Clone)]
 pub struct SlicedUnitAcc<
     const N: usize,
     const BIPOLAR: bool,
@@ -437,19 +604,51 @@ pub struct SlicedUnitAcc<
     // Plane 0 = Least Significant Bit, Plane PLANES-1 = Most Significant Bit.
     data: [[usize; N]; PLANES],
     count: usize,
-    rng: R,
+    rng: Option<R>, // None: ties hashed from the votes
 }
 
 impl<const N: usize, const BIPOLAR: bool, const PLANES: usize, R: Rng + SeedableRng + Default>
     Default for SlicedUnitAcc<N, BIPOLAR, PLANES, R>
 {
     fn default() -> Self {
+        Self::with_ties(None)
+    }
+}
+
+impl<const N: usize, const BIPOLAR: bool, const PLANES: usize, R: Rng>
+    SlicedUnitAcc<N, BIPOLAR, PLANES, R>
+{
+    /// Ties are broken with `rng`
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_ties(Some(rng))
+    }
+
+    fn with_ties(rng: Option<R>) -> Self {
         Self {
             data: [[0; N]; PLANES],
             count: 0,
-            rng: R::from_rng(&mut rand::rng()),
+            rng,
         }
     }
+
+    /// Number of vectors with bit j of word i set, per bit - as UnitAcc::votes.
+    /// Transposes the planes, like finalize.
+    pub fn votes(&self) -> [[VoteCount; usize::BITS as usize]; N] {
+        std::array::from_fn(|i| {
+            std::array::from_fn(|bit_pos| {
+                (0..PLANES.min(VoteCount::BITS as usize)).fold(0, |count, p| {
+                    count | ((((self.data[p][i] >> bit_pos) & 1) as VoteCount) << p)
+                })
+            })
+        })
+    }
+
+    pub fn with_seed(seed: u64) -> Self
+    where
+        R: SeedableRng,
+    {
+        Self::with_rng(R::seed_from_u64(seed))
+    }
 }
 
 impl<const N: usize, const BIPOLAR: bool, const PLANES: usize> UnitAccumulator<Binary<N, BIPOLAR>>
@@ -476,12 +675,48 @@ impl<const N: usize, const BIPOLAR: bool, const PLANES: usize> UnitAccumulator<B
         self.count += 1;
     }
 
+    /// Ripple-borrow subtraction, the reverse of add
+    fn subtract(&mut self, v: &Binary<N, BIPOLAR>) {
+        let mut borrow = v.data;
+        for p in 0..PLANES {
+            for (old_val_ref, borrow_word) in self.data[p].iter_mut().zip(borrow.iter_mut()) {
+                let old_val = *old_val_ref;
+                *old_val_ref = old_val ^ *borrow_word;
+                *borrow_word &= !old_val;
+            }
+            if borrow.iter().all(|&b| b == 0) {
+                break;
+            }
+        }
+        self.count -= 1;
+    }
+
+    /// Ripple-carry addition of the other counters, plane by plane - full adders
+    fn merge(&mut self, other: &Self) {
+        let mut carry = [0usize; N];
+        for p in 0..PLANES {
+            for ((word, &o), c) in self.data[p].iter_mut().zip(&other.data[p]).zip(&mut carry) {
+                let a = *word;
+                *word = a ^ o ^ *c;
+                *c = (a & o) | (*c & (a ^ o));
+            }
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> Binary<N, BIPOLAR> {
         let threshold = (self.count / 2) as u64;
         let is_even = self.count.is_multiple_of(2);
 
         let data = std::array::from_fn(|i| {
-            let tie_breaker: usize = self.rng.next_u64() as usize;
+            let tie_breaker = match &mut self.rng {
+                Some(rng) => rng.next_u64() as usize,
+                None => tie_bits(
+                    i,
+                    std::iter::once(self.count as u64)
+                        .chain(self.data.iter().map(|plane| plane[i] as u64)),
+                ),
+            };
             let mut word_acc = 0usize;
             // We process all 64 bits of the word simultaneously for each bit-position
             for bit_pos in 0..usize::BITS as usize {
@@ -663,8 +898,11 @@ pub fn save_hdvs_to_csv<const N: usize>(
 
 #[cfg(test)]
 mod tests {
-    use crate::types::binary::{Binary, Bipolar, UnitAcc, WeightedAcc};
+    use crate::types::binary::{
+        Binary, Bipolar, FixPointAcc, SlicedUnitAcc, UnitAcc, WeightedAcc, tie_bits,
+    };
     use crate::types::traits::{Accumulator, HyperVector, UnitAccumulator};
+    use mersenne_twister_rs::MersenneTwister64;
 
     #[test]
     fn test_accumulate_binary() {
@@ -689,6 +927,123 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    // v1 + v2 ties in every bit where they differ
+    fn tied_pair() -> (Binary<4>, Binary<4>) {
+        let v1 = Binary::<4>::from_slice(&[1; 256]);
+        let v2 = Binary::<4>::from_slice(&[0; 256]);
+        (v1, v2)
+    }
+
+    #[test]
+    fn test_reproducible_ties() {
+        let (v1, v2) = tied_pair();
+        let weighted = |seed| {
+            let mut acc = WeightedAcc::<4, false>::with_seed(seed);
+            acc.add(&v1, 1.0);
+            acc.add(&v2, 1.0);
+            acc.finalize()
+        };
+        let fixpoint = |seed| {
+            let mut acc = FixPointAcc::<4, false>::with_seed(seed);
+            acc.add(&v1, 0.5);
+            acc.add(&v2, 0.5);
+            acc.finalize()
+        };
+        let unit = |seed| {
+            let mut acc = UnitAcc::<4, false>::with_seed(seed);
+            acc.add(&v1);
+            acc.add(&v2);
+            acc.finalize()
+        };
+        let sliced = |seed| {
+            let mut acc = SlicedUnitAcc::<4, false, 4>::with_seed(seed);
+            acc.add(&v1);
+            acc.add(&v2);
+            acc.finalize()
+        };
+        for f in [
+            &weighted as &dyn Fn(u64) -> Binary<4>,
+            &fixpoint,
+            &unit,
+            &sliced,
+        ] {
+            assert_eq!(f(1), f(1));
+            assert_ne!(f(1), f(2));
+        }
+
+        // the default accumulators hash the ties from the votes
+        let bundle = || {
+            let mut acc = <Binary<4> as HyperVector>::Accumulator::default();
+            acc.add(&v1, 1.0);
+            acc.add(&v2, 1.0);
+            acc.finalize()
+        };
+        assert_eq!(bundle(), bundle());
+
+        // so bundles of unrelated pairs are unrelated - their ties are not shared
+        let mut mt = MersenneTwister64::new(5);
+        let mut pair = || {
+            let mut acc = <Binary<16> as HyperVector>::UnitAccumulator::default();
+            acc.add(&Binary::random(&mut mt));
+            acc.add(&Binary::random(&mut mt));
+            acc.finalize()
+        };
+        let (a, b) = (pair(), pair());
+        assert!((a.distance(&b) - 0.5).abs() < 0.06, "{}", a.distance(&b));
+    }
+
+    #[test]
+    fn test_ties_per_word() {
+        let votes = [0u64; 65];
+        assert_ne!(
+            tie_bits(0, votes.into_iter()),
+            tie_bits(1, votes.into_iter())
+        );
+
+        // every word of the tied pair has the same votes, and gets its own ties
+        let (v1, v2) = tied_pair();
+        let mut weighted = WeightedAcc::<4, false>::default();
+        weighted.add(&v1, 1.0);
+        weighted.add(&v2, 1.0);
+        let mut fixpoint = FixPointAcc::<4, false>::default();
+        fixpoint.add(&v1, 0.5);
+        fixpoint.add(&v2, 0.5);
+        let mut unit = UnitAcc::<4, false>::default();
+        unit.add(&v1);
+        unit.add(&v2);
+        let mut sliced = SlicedUnitAcc::<4, false, 4>::default();
+        sliced.add(&v1);
+        sliced.add(&v2);
+        for v in [
+            weighted.finalize(),
+            fixpoint.finalize(),
+            unit.finalize(),
+            sliced.finalize(),
+        ] {
+            for i in 1..4 {
+                assert!(!v.data[..i].contains(&v.data[i]), "{:x?}", v.data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sliced_votes() {
+        let v1 = Binary::<1>::from_slice(&[1, 0, 1, 0, 0, 0, 0, 0]);
+        let v2 = Binary::<1>::from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        let v3 = Binary::<1>::from_slice(&[1, 0, 0, 1, 0, 0, 0, 0]);
+        let mut sliced = SlicedUnitAcc::<1, false, 4>::default();
+        let mut unit = UnitAcc::<1, false>::default();
+        for v in [&v1, &v2, &v3, &v3] {
+            sliced.add(v);
+            unit.add(v);
+        }
+        sliced.subtract(&v1);
+        unit.subtract(&v1);
+        assert_eq!(&sliced.votes()[0][..4], &[3, 0, 0, 2]);
+        assert_eq!(&unit.votes()[0][..4], &[3, 0, 0, 2]);
+        assert_eq!(sliced.count(), 3);
+    }
+
     #[test]
     fn test_accumulate_bipolar() {
         let v1 = Bipolar::<1>::from_slice(&[-1, 1, -1, 1, 1, 1, 1, 1]);
@@ -3,7 +3,6 @@ use rand::Rng;
 use rand_distr::{Distribution, Normal};
 use rustfft::{FftPlanner, num_complex::Complex};
 use std::cell::RefCell;
-use std::fs::File;
 use std::io::{Read, Write};
 use std::mem::size_of;
 
@@ -22,6 +21,7 @@ impl<const N: usize> HyperVector for ComplexHDV<N> {
     type UnitAccumulator = UnitAcc<N>;
     type Element = Complex<f32>;
     const DIM: usize = N;
+    const TAG: &'static str = "complex";
 
     fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         // Set stddev so that E[‖z‖^2] = 1
@@ -118,23 +118,23 @@ impl<const N: usize> HyperVector for ComplexHDV<N> {
         out
     }
 
-    fn write(&self, file: &mut File) -> std::io::Result<()> {
+    fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
         for &value in &self.data {
-            file.write_all(&value.re.to_ne_bytes())?;
-            file.write_all(&value.im.to_ne_bytes())?;
+            writer.write_all(&value.re.to_le_bytes())?;
+            writer.write_all(&value.im.to_le_bytes())?;
         }
         Ok(())
     }
 
-    fn read(file: &mut File) -> std::io::Result<Self> {
+    fn read(reader: &mut impl Read) -> std::io::Result<Self> {
         let mut data = [Complex::<f32>::new(0.0, 0.0); N];
         for slot in &mut data {
             let mut re_buf = [0u8; size_of::<f32>()];
             let mut im_buf = [0u8; size_of::<f32>()];
-            file.read_exact(&mut re_buf)?;
-            file.read_exact(&mut im_buf)?;
-            let re = f32::from_ne_bytes(re_buf);
-            let im = f32::from_ne_bytes(im_buf);
+            reader.read_exact(&mut re_buf)?;
+            reader.read_exact(&mut im_buf)?;
+            let re = f32::from_le_bytes(re_buf);
+            let im = f32::from_le_bytes(im_buf);
             *slot = Complex::new(re, im);
         }
         Ok(Self { data })
@@ -286,6 +286,13 @@ impl<const N: usize> Default for WeightedAccumulator<N> {
     }
 }
 
+impl<const N: usize> WeightedAccumulator<N> {
+    /// Weighted sum of the vectors added
+    pub fn sums(&self) -> &[Complex<f32>; N] {
+        &self.sum
+    }
+}
+
 impl<const N: usize> Accumulator<ComplexHDV<N>> for WeightedAccumulator<N> {
     fn new() -> Self {
         Self {
@@ -301,6 +308,20 @@ impl<const N: usize> Accumulator<ComplexHDV<N>> for WeightedAccumulator<N> {
         self.count += weight as f32
     }
 
+    fn scale(&mut self, factor: f64) {
+        for s in self.sum.iter_mut() {
+            *s *= factor as f32;
+        }
+        self.count *= factor.abs() as f32;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sum.iter_mut().zip(&other.sum) {
+            *s += o;
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> ComplexHDV<N> {
         let data: [Complex<f32>; N] = std::array::from_fn(|i| self.sum[i] / self.count.sqrt());
         ComplexHDV { data }
@@ -339,6 +360,13 @@ impl<const N: usize> Default for UnitAcc<N> {
     }
 }
 
+impl<const N: usize> UnitAcc<N> {
+    /// Sum of the vectors added
+    pub fn sums(&self) -> &[Complex<f32>; N] {
+        &self.sum
+    }
+}
+
 impl<const N: usize> UnitAccumulator<ComplexHDV<N>> for UnitAcc<N> {
     fn new() -> Self {
         Self {
@@ -354,6 +382,20 @@ impl<const N: usize> UnitAccumulator<ComplexHDV<N>> for UnitAcc<N> {
         self.count += 1
     }
 
+    fn subtract(&mut self, v: &ComplexHDV<N>) {
+        for i in 0..N {
+            self.sum[i] -= v.data[i];
+        }
+        self.count -= 1
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sum.iter_mut().zip(&other.sum) {
+            *s += o;
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> ComplexHDV<N> {
         let data: [Complex<f32>; N] =
             std::array::from_fn(|i| self.sum[i] / (self.count as f32).sqrt());
@@ -3,7 +3,6 @@
 
 use crate::types::traits::{Accumulator, HyperVector, UnitAccumulator};
 use rand::Rng;
-use std::fs::File;
 use std::io::{Read, Write};
 
 const R: u8 = 8; // bits per component (1-8), e.g. R=8 => MODULUS=256, HALF=128
@@ -58,6 +57,7 @@ impl<const DIM: usize> HyperVector for Modular<DIM> {
     type UnitAccumulator = UnitAcc<DIM>;
     type Element = u8;
     const DIM: usize = DIM;
+    const TAG: &'static str = "modular";
 
     fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let data = std::array::from_fn(|_| (rng.next_u32() & (MASK as u32)) as u8);
@@ -124,13 +124,13 @@ impl<const DIM: usize> HyperVector for Modular<DIM> {
         self.data.iter().map(|&e| e as f32).collect()
     }
 
-    fn write(&self, file: &mut File) -> std::io::Result<()> {
-        file.write_all(&self.data)
+    fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writer.write_all(&self.data)
     }
 
-    fn read(file: &mut File) -> std::io::Result<Self> {
+    fn read(reader: &mut impl Read) -> std::io::Result<Self> {
         let mut data = [0u8; DIM];
-        file.read_exact(&mut data)?;
+        reader.read_exact(&mut data)?;
         let data = data.map(|b| b & MASK); // Re-mask in case file was written with different R
         Ok(Self { data })
     }
@@ -147,6 +147,22 @@ impl<const DIM: usize> HyperVector for Modular<DIM> {
     }
 }
 
+// The phase of the point (cos, sin), mapped to the nearest of the MODULUS discrete
+// angles - 0 at the origin where the phase is undefined.
+pub(crate) fn quantize_phase(sin: f32, cos: f32) -> u8 {
+    if sin.abs() < f32::EPSILON && cos.abs() < f32::EPSILON {
+        0
+    } else {
+        let angle = sin.atan2(cos);
+        // Normalize -PI..PI to 0.0..1.0
+        let normalized = (angle / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
+
+        // map to the nearest discrete u8 gate
+        let val = (normalized * MODULUS as f32).round() as u32;
+        (val % MODULUS) as u8
+    }
+}
+
 #[derive(Clone)]
 pub struct WeightedAccumulator<const D: usize> {
     // We track sums of Sines and Cosines to find the circular mean
@@ -163,6 +179,13 @@ impl<const D: usize> Default for WeightedAccumulator<D> {
     }
 }
 
+impl<const D: usize> WeightedAccumulator<D> {
+    /// Weighted sums of the sines and cosines of the phases added
+    pub fn sums(&self) -> (&[f32; D], &[f32; D]) {
+        (&self.sums_sin, &self.sums_cos)
+    }
+}
+
 impl<const D: usize> Accumulator<Modular<D>> for WeightedAccumulator<D> {
     fn new() -> Self {
         Self {
@@ -188,20 +211,27 @@ impl<const D: usize> Accumulator<Modular<D>> for WeightedAccumulator<D> {
         self.count += weight;
     }
 
-    fn finalize(&mut self) -> Modular<D> {
-        let data = std::array::from_fn(|i| {
-            if self.sums_sin[i].abs() < f32::EPSILON && self.sums_cos[i].abs() < f32::EPSILON {
-                0
-            } else {
-                let angle = self.sums_sin[i].atan2(self.sums_cos[i]);
-                // Normalize -PI..PI to 0.0..1.0
-                let normalized = (angle / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
+    fn scale(&mut self, factor: f64) {
+        let f = factor as f32;
+        for (s, c) in self.sums_sin.iter_mut().zip(self.sums_cos.iter_mut()) {
+            *s *= f;
+            *c *= f;
+        }
+        self.count *= factor.abs();
+    }
 
-                // map to the nearest discrete u8 gate
-                let val = (normalized * MODULUS as f32).round() as u32;
-                (val % MODULUS) as u8
-            }
-        });
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sums_sin.iter_mut().zip(other.sums_sin.iter()) {
+            *s += o;
+        }
+        for (c, o) in self.sums_cos.iter_mut().zip(other.sums_cos.iter()) {
+            *c += o;
+        }
+        self.count += other.count;
+    }
+
+    fn finalize(&mut self) -> Modular<D> {
+        let data = std::array::from_fn(|i| quantize_phase(self.sums_sin[i], self.sums_cos[i]));
         Modular { data }
     }
 
@@ -224,6 +254,13 @@ impl<const D: usize> Default for UnitAcc<D> {
     }
 }
 
+impl<const D: usize> UnitAcc<D> {
+    /// Sums of the sines and cosines of the phases added
+    pub fn sums(&self) -> (&[f32; D], &[f32; D]) {
+        (&self.sums_sin, &self.sums_cos)
+    }
+}
+
 impl<const D: usize> UnitAccumulator<Modular<D>> for UnitAcc<D> {
     fn new() -> Self {
         Self {
@@ -243,20 +280,28 @@ impl<const D: usize> UnitAccumulator<Modular<D>> for UnitAcc<D> {
         self.count += 1;
     }
 
-    fn finalize(&mut self) -> Modular<D> {
-        let data = std::array::from_fn(|i| {
-            if self.sums_sin[i].abs() < f32::EPSILON && self.sums_cos[i].abs() < f32::EPSILON {
-                0
-            } else {
-                let angle = self.sums_sin[i].atan2(self.sums_cos[i]);
-                // Normalize -PI..PI to 0.0..1.0
-                let normalized = (angle / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
+    fn subtract(&mut self, v: &Modular<D>) {
+        let t = sincos_tables();
+        for i in 0..D {
+            let idx = v.data[i] as usize;
+            self.sums_sin[i] -= t.sin[idx];
+            self.sums_cos[i] -= t.cos[idx];
+        }
+        self.count -= 1;
+    }
 
-                // map to the nearest discrete u8 gate
-                let val = (normalized * MODULUS as f32).round() as u32;
-                (val % MODULUS) as u8
-            }
-        });
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sums_sin.iter_mut().zip(other.sums_sin.iter()) {
+            *s += o;
+        }
+        for (c, o) in self.sums_cos.iter_mut().zip(other.sums_cos.iter()) {
+            *c += o;
+        }
+        self.count += other.count;
+    }
+
+    fn finalize(&mut self) -> Modular<D> {
+        let data = std::array::from_fn(|i| quantize_phase(self.sums_sin[i], self.sums_cos[i]));
         Modular { data }
     }
 
@@ -3,7 +3,6 @@ use rand::Rng;
 use rand_distr::{Distribution, Normal};
 use rustfft::{FftPlanner, num_complex::Complex};
 use std::cell::RefCell;
-use std::fs::File;
 use std::io::{self, Read, Write};
 use std::mem::size_of;
 
@@ -22,6 +21,7 @@ impl<const N: usize> HyperVector for RealHDV<N> {
     type UnitAccumulator = UnitAcc<N>;
     type Element = f32;
     const DIM: usize = N;
+    const TAG: &'static str = "real";
 
     fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let stddev = 1.0 / (N as f64).sqrt();
@@ -80,19 +80,19 @@ impl<const N: usize> HyperVector for RealHDV<N> {
         self.data.iter().map(|&e| e as f32).collect()
     }
 
-    fn write(&self, file: &mut File) -> io::Result<()> {
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
         for &value in &self.data {
-            file.write_all(&value.to_ne_bytes())?;
+            writer.write_all(&value.to_le_bytes())?;
         }
         Ok(())
     }
 
-    fn read(file: &mut File) -> io::Result<Self> {
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
         let mut data = [0f64; N];
         for slot in &mut data {
             let mut buf = [0u8; size_of::<f64>()];
-            file.read_exact(&mut buf)?;
-            *slot = f64::from_ne_bytes(buf);
+            reader.read_exact(&mut buf)?;
+            *slot = f64::from_le_bytes(buf);
         }
         Ok(Self { data })
     }
@@ -234,6 +234,13 @@ impl<const N: usize> Default for WeightedAccumulator<N> {
     }
 }
 
+impl<const N: usize> WeightedAccumulator<N> {
+    /// Weighted sum of the vectors added
+    pub fn sums(&self) -> &[f64; N] {
+        &self.sum
+    }
+}
+
 impl<const N: usize> Accumulator<RealHDV<N>> for WeightedAccumulator<N> {
     fn new() -> Self {
         Self {
@@ -249,6 +256,20 @@ impl<const N: usize> Accumulator<RealHDV<N>> for WeightedAccumulator<N> {
         self.count += weight;
     }
 
+    fn scale(&mut self, factor: f64) {
+        for s in self.sum.iter_mut() {
+            *s *= factor;
+        }
+        self.count *= factor.abs();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sum.iter_mut().zip(&other.sum) {
+            *s += o;
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> RealHDV<N> {
         let data: [f64; N] = std::array::from_fn(|i| self.sum[i] / self.count.sqrt());
         RealHDV { data }
@@ -271,6 +292,13 @@ impl<const N: usize> Default for UnitAcc<N> {
     }
 }
 
+impl<const N: usize> UnitAcc<N> {
+    /// Sum of the vectors added
+    pub fn sums(&self) -> &[f64; N] {
+        &self.sum
+    }
+}
+
 impl<const N: usize> UnitAccumulator<RealHDV<N>> for UnitAcc<N> {
     fn new() -> Self {
         Self {
@@ -286,6 +314,20 @@ impl<const N: usize> UnitAccumulator<RealHDV<N>> for UnitAcc<N> {
         self.count += 1;
     }
 
+    fn subtract(&mut self, v: &RealHDV<N>) {
+        for i in 0..N {
+            self.sum[i] -= v.data[i];
+        }
+        self.count -= 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (s, o) in self.sum.iter_mut().zip(&other.sum) {
+            *s += o;
+        }
+        self.count += other.count;
+    }
+
     fn finalize(&mut self) -> RealHDV<N> {
         let data: [f64; N] = std::array::from_fn(|i| self.sum[i] / (self.count as f64).sqrt());
         RealHDV { data }
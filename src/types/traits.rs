@@ -1,25 +1,55 @@
 use rand::Rng;
-use std::fs::File;
+use std::io::{Read, Write};
 
 pub trait UnitAccumulator<T: HyperVector> {
     fn new() -> Self;
     fn add(&mut self, v: &T);
+    /// Removes a vector added earlier, e.g. the oldest one of a sliding window.
+    /// Subtracting a vector that was never added leaves the counters undefined.
+    fn subtract(&mut self, v: &T);
+    /// Adds everything added to `other` - partial sums from several threads can be
+    /// merged into one
+    fn merge(&mut self, other: &Self);
     fn finalize(&mut self) -> T;
     fn count(&self) -> usize;
+
+    /// The vector finalize would give now, leaving the accumulator as is
+    fn snapshot(&self) -> T
+    where
+        Self: Clone,
+    {
+        self.clone().finalize()
+    }
 }
 
 pub trait Accumulator<T: HyperVector> {
     fn new() -> Self;
     fn add(&mut self, v: &T, weight: f64);
+    /// Multiplies everything added so far by `factor` - with 0 < factor < 1 after every
+    /// batch, older vectors fade out exponentially
+    fn scale(&mut self, factor: f64);
+    /// Adds everything added to `other` - partial sums from several threads can be
+    /// merged into one
+    fn merge(&mut self, other: &Self);
     fn finalize(&mut self) -> T;
     fn count(&self) -> f64;
+
+    /// The vector finalize would give now, leaving the accumulator as is
+    fn snapshot(&self) -> T
+    where
+        Self: Clone,
+    {
+        self.clone().finalize()
+    }
 }
 
 pub trait HyperVector: Sized + Clone {
-    type Accumulator: Default + Accumulator<Self>;
-    type UnitAccumulator: Default + UnitAccumulator<Self>;
+    type Accumulator: Default + Accumulator<Self> + Send;
+    type UnitAccumulator: Default + UnitAccumulator<Self> + Send;
     type Element;
     const DIM: usize;
+    /// Stable name of the type in files - with DIM it identifies the type
+    const TAG: &'static str;
 
     fn random<R: Rng + ?Sized>(rng: &mut R) -> Self;
     /// Returns the identity element of the hypervector space:
@@ -57,8 +87,9 @@ pub trait HyperVector: Sized + Clone {
     fn norm(&self) -> f32;
 
     fn unpack(&self) -> Vec<f32>;
-    fn write(&self, file: &mut File) -> std::io::Result<()>;
-    fn read(file: &mut File) -> std::io::Result<Self>;
+    /// Writes the elements, little endian
+    fn write(&self, writer: &mut impl Write) -> std::io::Result<()>;
+    fn read(reader: &mut impl Read) -> std::io::Result<Self>;
     fn from_slice(slice: &[Self::Element]) -> Self;
     fn from_iter(iter: impl Iterator<Item = Self::Element>) -> Self;
 }